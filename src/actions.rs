@@ -0,0 +1,130 @@
+use crate::command_handler::VERBS;
+use crate::game::Game;
+
+/// Lists the commands worth trying right now: every verb applied to the
+/// objects, characters and open doors that are in scope.
+pub fn valid_actions(game: &Game) -> Vec<String> {
+    let player = &game.player;
+    let room = player.get_curr_room(&game.rooms);
+    let mut actions = vec!["look".to_owned(), "inventory".to_owned()];
+
+    for door in room.doors.iter().filter(|door| door.is_open) {
+        actions.push(door.direction.to_owned());
+    }
+
+    for object in &room.inventory.objects {
+        actions.push(format!("examine {}", object.name));
+        if object.can_take {
+            actions.push(format!("take {}", object.name));
+        }
+        actions.push(format!("use {}", object.name));
+    }
+    for object in &player.inventory.objects {
+        actions.push(format!("examine {}", object.name));
+        actions.push(format!("use {}", object.name));
+    }
+
+    // containers can be in the room or in the player's pockets
+    let containers = room
+        .inventory
+        .objects
+        .iter()
+        .chain(&player.inventory.objects);
+    for container in containers {
+        for object in &player.inventory.objects {
+            if container.accepts.iter().any(|name| name == object.name) {
+                actions.push(format!("put {} in {}", object.name, container.name));
+            }
+        }
+    }
+
+    for character in &room.characters {
+        actions.push(format!("examine {}", character.name));
+        actions.push(format!("talk to {}", character.name));
+        for topic in &character.dialogue.topics {
+            actions.push(format!("ask {} about {}", character.name, topic.name));
+        }
+        for object in &character.inventory.objects {
+            actions.push(format!("ask {} for {}", character.name, object.name));
+        }
+        for object in &player.inventory.objects {
+            actions.push(format!("give {} to {}", object.name, character.name));
+        }
+        if character.will_follow {
+            let order = if character.is_following {
+                "stay"
+            } else {
+                "follow"
+            };
+            actions.push(format!("tell {} to {}", character.name, order));
+        }
+    }
+
+    // numbered choices in an ongoing conversation
+    let talking_to = game
+        .state
+        .talking_to
+        .and_then(|name| room.find_character(name));
+    if let Some(character) = talking_to {
+        let dialogue = &character.dialogue;
+        if let Some(node) = dialogue.current {
+            let choices = dialogue.available_choices(node, player, &game.rooms, &game.state);
+            for i in 1..=choices.len() {
+                actions.push(i.to_string());
+            }
+        }
+    }
+
+    actions
+}
+
+/// Finds ways to finish the end of a partly typed line. Returns where the
+/// completed text starts in `line`, and the candidates, e.g. "take te" offers
+/// "tea bag" and "tea tin" from position 5.
+pub fn complete(game: &Game, line: &str) -> (usize, Vec<String>) {
+    let first_word_ends = line.find(' ');
+    let words: Vec<String> = match first_word_ends {
+        None => VERBS.iter().map(|verb| verb.name.to_owned()).collect(),
+        Some(_) => {
+            let player = &game.player;
+            let room = player.get_curr_room(&game.rooms);
+            let objects = room
+                .inventory
+                .objects
+                .iter()
+                .chain(&player.inventory.objects);
+            objects
+                .map(|object| object.name)
+                .chain(room.characters.iter().map(|character| character.name))
+                .chain(
+                    room.doors
+                        .iter()
+                        .filter(|door| door.is_open)
+                        .map(|door| door.direction),
+                )
+                .map(str::to_owned)
+                .collect()
+        }
+    };
+
+    // names can be several words long, so try the longest unfinished tail first
+    let first_start = first_word_ends.map(|end| end + 1).unwrap_or(0);
+    let tails = line
+        .char_indices()
+        .filter(|(i, c)| *c == ' ' && *i >= first_start)
+        .map(|(i, _)| i + 1);
+    for start in std::iter::once(first_start).chain(tails) {
+        let partial = line[start..].to_lowercase();
+        let mut candidates: Vec<String> = words
+            .iter()
+            .filter(|word| word.starts_with(&partial))
+            .cloned()
+            .collect();
+        if !candidates.is_empty() {
+            candidates.sort();
+            candidates.dedup();
+            return (start, candidates);
+        }
+    }
+    (line.len(), vec![])
+}
@@ -0,0 +1,241 @@
+use crate::game::Game;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Somewhere sounds can be sent. Sounds are named by their file paths.
+pub trait Backend {
+    /// Starts a looping track, fading out whatever loop was playing over `fade`
+    fn play_loop(&mut self, track: &'static str, fade: Duration);
+    fn stop_loop(&mut self, fade: Duration);
+    fn play_once(&mut self, effect: &'static str);
+    /// 0.0 is silent, 1.0 is full volume
+    fn set_volume(&mut self, volume: f32);
+}
+
+/// Plays nothing, for when audio is turned off or there's no device to play on
+pub struct NullBackend;
+
+impl Backend for NullBackend {
+    fn play_loop(&mut self, _track: &'static str, _fade: Duration) {}
+    fn stop_loop(&mut self, _fade: Duration) {}
+    fn play_once(&mut self, _effect: &'static str) {}
+    fn set_volume(&mut self, _volume: f32) {}
+}
+
+/// Something a backend was asked to do
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cue {
+    Loop(&'static str),
+    StopLoop,
+    Once(&'static str),
+    Volume(f32),
+}
+
+/// Writes down every cue instead of playing it, so tests can check what was heard.
+/// Clones share the same list.
+#[derive(Clone, Default)]
+pub struct RecordingBackend {
+    pub cues: Arc<Mutex<Vec<Cue>>>,
+}
+
+impl RecordingBackend {
+    /// Everything recorded so far, clearing the list
+    pub fn take(&self) -> Vec<Cue> {
+        self.cues.lock().unwrap().drain(..).collect()
+    }
+}
+
+impl Backend for RecordingBackend {
+    fn play_loop(&mut self, track: &'static str, _fade: Duration) {
+        self.cues.lock().unwrap().push(Cue::Loop(track));
+    }
+
+    fn stop_loop(&mut self, _fade: Duration) {
+        self.cues.lock().unwrap().push(Cue::StopLoop);
+    }
+
+    fn play_once(&mut self, effect: &'static str) {
+        self.cues.lock().unwrap().push(Cue::Once(effect));
+    }
+
+    fn set_volume(&mut self, volume: f32) {
+        self.cues.lock().unwrap().push(Cue::Volume(volume));
+    }
+}
+
+/// Keeps the soundtrack in step with the game: each room's ambience, the
+/// sounds the world cues up, and the player's music settings.
+pub struct Audio {
+    backend: Box<dyn Backend>,
+    /// whether ambient loops play; effects play either way
+    music: bool,
+    volume: f32,
+    playing: Option<&'static str>,
+    /// how long one room's ambience takes to fade into the next
+    pub crossfade: Duration,
+}
+
+impl Audio {
+    pub fn new(backend: Box<dyn Backend>) -> Self {
+        Audio {
+            backend,
+            music: true,
+            volume: 1.0,
+            playing: None,
+            crossfade: Duration::from_secs(2),
+        }
+    }
+
+    pub fn silent() -> Self {
+        Audio::new(Box::new(NullBackend))
+    }
+
+    /// Plays whatever the last turn called for. Call after every step.
+    pub fn update(&mut self, game: &Game) {
+        let ambience = game.player.get_curr_room(&game.rooms).ambience;
+        if self.music && ambience != self.playing {
+            match ambience {
+                Some(track) => self.backend.play_loop(track, self.crossfade),
+                None => self.backend.stop_loop(self.crossfade),
+            }
+            self.playing = ambience;
+        }
+        for sound in &game.state.sounds {
+            self.backend.play_once(sound);
+        }
+    }
+
+    /// Plays a sound outside of the world's turns, e.g. in a cutscene
+    pub fn play_once(&mut self, effect: &'static str) {
+        self.backend.play_once(effect);
+    }
+
+    /// Handles `music on`, `music off` and `volume <0-10>`. Returns a reply if
+    /// the input was one of them, or `None` if it's for the game.
+    pub fn command(&mut self, input: &str, game: &Game) -> Option<String> {
+        let words: Vec<String> = input.split_whitespace().map(str::to_lowercase).collect();
+        let words: Vec<&str> = words.iter().map(String::as_str).collect();
+        match words.as_slice() {
+            ["music", "on"] => {
+                self.music = true;
+                self.update(game);
+                Some("Music on.".to_owned())
+            }
+            ["music", "off"] => {
+                self.music = false;
+                if self.playing.take().is_some() {
+                    self.backend.stop_loop(self.crossfade);
+                }
+                Some("Music off.".to_owned())
+            }
+            ["music"] => Some("Type MUSIC ON or MUSIC OFF.".to_owned()),
+            ["volume"] => Some(format!("The volume is {}.", (self.volume * 10.0).round())),
+            ["volume", level] => match level.parse::<u8>() {
+                Ok(level) if level <= 10 => {
+                    self.volume = f32::from(level) / 10.0;
+                    self.backend.set_volume(self.volume);
+                    Some(format!("Volume set to {}.", level))
+                }
+                _ => Some("The volume goes from 0 to 10.".to_owned()),
+            },
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "audio")]
+pub use self::rodio_backend::RodioBackend;
+
+#[cfg(feature = "audio")]
+mod rodio_backend {
+    use super::Backend;
+    use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink, Source};
+    use std::fs::File;
+    use std::io::BufReader;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    const FADE_STEPS: u32 = 20;
+
+    /// Plays through the default audio device with rodio
+    pub struct RodioBackend {
+        // sounds stop when the stream is dropped
+        _stream: OutputStream,
+        handle: OutputStreamHandle,
+        ambience: Option<Arc<Sink>>,
+        volume: f32,
+    }
+
+    impl RodioBackend {
+        /// Returns `None` if there's no audio device to play on
+        pub fn try_default() -> Option<Self> {
+            let (stream, handle) = OutputStream::try_default().ok()?;
+            Some(RodioBackend {
+                _stream: stream,
+                handle,
+                ambience: None,
+                volume: 1.0,
+            })
+        }
+
+        /// Missing or unreadable files are skipped, so a world can be played without its sounds
+        fn sink_for(&self, path: &str, repeat: bool) -> Option<Sink> {
+            let file = File::open(path).ok()?;
+            let source = Decoder::new(BufReader::new(file)).ok()?;
+            let sink = Sink::try_new(&self.handle).ok()?;
+            if repeat {
+                sink.append(source.repeat_infinite());
+            } else {
+                sink.append(source);
+            }
+            Some(sink)
+        }
+    }
+
+    /// Moves a sink's volume from `from` to `to` over `duration`, in the background
+    fn fade(sink: Arc<Sink>, from: f32, to: f32, duration: Duration, stop_after: bool) {
+        thread::spawn(move || {
+            for step in 1..=FADE_STEPS {
+                thread::sleep(duration / FADE_STEPS);
+                sink.set_volume(from + (to - from) * step as f32 / FADE_STEPS as f32);
+            }
+            if stop_after {
+                sink.stop();
+            }
+        });
+    }
+
+    impl Backend for RodioBackend {
+        fn play_loop(&mut self, track: &'static str, fade_for: Duration) {
+            self.stop_loop(fade_for);
+            if let Some(sink) = self.sink_for(track, true) {
+                let sink = Arc::new(sink);
+                sink.set_volume(0.0);
+                fade(sink.clone(), 0.0, self.volume, fade_for, false);
+                self.ambience = Some(sink);
+            }
+        }
+
+        fn stop_loop(&mut self, fade_for: Duration) {
+            if let Some(sink) = self.ambience.take() {
+                let volume = sink.volume();
+                fade(sink, volume, 0.0, fade_for, true);
+            }
+        }
+
+        fn play_once(&mut self, effect: &'static str) {
+            if let Some(sink) = self.sink_for(effect, false) {
+                sink.set_volume(self.volume);
+                sink.detach();
+            }
+        }
+
+        fn set_volume(&mut self, volume: f32) {
+            self.volume = volume;
+            if let Some(sink) = &self.ambience {
+                sink.set_volume(volume);
+            }
+        }
+    }
+}
@@ -1,341 +1,342 @@
-use rodio::Source;
 use std::fs::File;
-use std::io;
-use std::io::BufReader;
-use std::io::Write;
-use std::time::Instant;
-use std::{thread, time};
-use text_engine::character::Character;
-use text_engine::command_handler::CommandHandler;
-use text_engine::door::Door;
-use text_engine::entity::Object;
-use text_engine::game_state::GameState;
-use text_engine::inventory::Inventory;
-use text_engine::parser::Parser;
-use text_engine::player::Player;
-use text_engine::room::{Room, RoomID};
-use text_engine::util::{get_room_name_border, get_trimmed_input};
+use std::io::{self, BufReader, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::{env, fs, mem, process, thread};
+#[cfg(feature = "line-editor")]
+use text_engine::actions::complete;
+use text_engine::audio::Audio;
+#[cfg(feature = "audio")]
+use text_engine::audio::RodioBackend;
+use text_engine::cli::{Format, Options, USAGE};
+use text_engine::cutscene::{Cutscene, Renderer};
+use text_engine::game::{Ending, Game};
+#[cfg(feature = "line-editor")]
+use text_engine::line_editor::{self, LineEditor};
+use text_engine::protocol;
+use text_engine::save::Save;
+#[cfg(feature = "tui")]
+use text_engine::tui::Tui;
+use text_engine::util::get_trimmed_input;
+use text_engine::worlds::{file, tea_time};
 
-const GAME_LENGTH: u64 = 300;
-const DOOR_CLOSED_LENGTH: u64 = 180;
-const TEA_BREW_LENGTH: u64 = 60;
+// where SAVE writes when no file is named
+const SAVE_FILE: &str = "tea_time.save";
 
-fn get_living_room_items() -> Inventory {
-    Inventory {
-        objects: vec![
-            Object {
-                name: "couch",
-                desc: "A fluffy light grey couch. It's so comfortable that you sometimes unknowingly doze off on its cushions.",
-                ..Default::default()
-            },
-            Object {
-                name: "coffee table",
-                desc: "The amber surface of the table is stained with faint traces of old coffee and tea mugs.",
-                inventory: vec!["watch".into()],
-                ..Default::default()
-            },
-            Object {
-                name: "watch",
-                desc: "A simple analog watch with a thin gold band. You take comfort in the fact that if you ever need to know the time, you can USE the watch.",
-                can_take: false,
-                can_use: true,
-                ..Default::default()
-            },
-        ],
+// commands are remembered between games, in the player's home directory
+#[cfg(feature = "line-editor")]
+const HISTORY_FILE: &str = ".tea_time_history";
+
+fn main() {
+    let options = match Options::parse(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+    if options.tui && !cfg!(feature = "tui") {
+        eprintln!("This build has no TUI. Rebuild with `cargo build --features tui`.");
+        process::exit(2);
     }
-}
 
-fn get_kitchen_items() -> Inventory {
-    Inventory {
-        objects: vec![
-            Object {
-                name: "counter",
-                desc: "Your friendly philodendron sits idly on the countertop, keeping the remnants of this morning's half-eaten breakfast company. A kettle and a tea tin rest on the other side of the sink.",
-                inventory: vec!["water".into()],
-                ..Default::default()
-            },
-            Object {
-                name: "kettle",
-                desc: "Your trusty electric kettle. Sees USE almost every morning, as well as some unfortunate nights.",
-                accepts: vec!["water".into()],
-                ..Default::default()
-            },
-            Object {
-                name: "tea tin",
-                desc: "A delicate purple-hued tin box with a vaguely English air. The label reads `Harney & Son's Earl Grey Tea Sachets`. It also tells you that this is special tea and must be brewed for 60 minutes.",
-                inventory: vec!["tea bag".into()],
-                can_take: false,
-                ..Default::default()
-            },
-            Object {
-                name: "tea bag",
-                desc: "A silk tea bag with dark leaves inside. The blueberry maple aroma of the tea comforts you.",
-                ..Default::default()
-            },
-            Object {
-                name: "water",
-                desc: "Water, the source of life! Straight from the Brita.",
-                ..Default::default()
-            },
-            Object {
-                name: "cupboard",
-                desc: "A white-framed cupboard. You can see your growing mug collection through the glass panes.",
-                inventory: vec!["mug".into()],
-                ..Default::default()
-            },
-            Object {
-                name: "mug",
-                desc: "Your favorite mug. It fits snugly into your hand. A small outline of a rabbit is painted on the side.",
-                accepts: vec!["tea bag".into(), "hot water".into(), "sugar".into()],
-                can_take: false,
-                ..Default::default()
-            },
-        ],
+    // get world, picking up where a save left off
+    let save = options
+        .load
+        .as_ref()
+        .map(|path| Save::read(path).unwrap_or_else(|e| fail(&e)));
+    let world = options
+        .world
+        .clone()
+        .or_else(|| save.as_ref()?.world.as_ref().map(PathBuf::from));
+    let mut game = match &world {
+        Some(path) => file::load(path, options.clock()).unwrap_or_else(|e| fail(&e)),
+        None => tea_time::new_game(options.clock()),
+    };
+    if let Some(seed) = options.seed {
+        game.state.reseed(seed);
+    }
+    game.debug = options.debug;
+    if let Some(save) = &save {
+        save.replay(&mut game);
     }
-}
 
-fn get_rooms() -> [Room; 4] {
-    // living room 0, kitchen 1, hallway 2, cat room 3
-    [
-        Room {
-            name: "Living Room",
-            desc: "Your small but cozy living room. A light grey couch is nestled into the far corner, and a coffee table sits comfortably at its feet. The front door of your apartment lies to the north, and the kitchen door lies to the east.",
-            doors: vec![
-                Door {
-                    target: RoomID(1),
-                    direction: "east",
-                    ..Default::default()
-                },
-                Door {
-                    target: RoomID(2),
-                    direction: "north",
-                    msg_on_open: Some("You step into the hallway."),
-                    ..Default::default()
-                },
-            ],
-            inventory: get_living_room_items(),
-            characters: vec![],
-        },
-        Room {
-            name: "Kitchen",
-            desc:
-                "A full moon glow illuminates the room from the window above the sink. Beside the window is a cupboard full of kitchenware. Various items lay on the kitchen counter. The door to the living room lies to the west.",
-            doors: vec![Door {
-                target: RoomID(0),
-                direction: "west",
-                ..Default::default()
-            }],
-            inventory: get_kitchen_items(),
-            characters: vec![],
-        },
-        Room {
-            name: "Hallway",
-            desc: "The forest green walls of the corridor are decorated with black and white photos, eclectic paintings, and old 70s movie posters. It smells a bit musty. Your front door is to the south, and across the hall is the door to Unit 11.",
-            doors: vec![
-                Door {
-                    target: RoomID(0),
-                    direction: "south",
-                    ..Default::default()
-                },
-                Door {
-                    target: RoomID(3),
-                    direction: "north",
-                    is_open: false,
-                    msg_on_open: Some("You knock hesitantly. As soon as your hand makes contact with the door, it slowly creaks open."),
-                    msg_on_closed: Some("A note is on the door. It reads `I'll be back at 10:00pm.`"),
-                },
-            ],
-            inventory: Inventory::default(),
-            characters: vec![],
-        },
-        Room {
-            name: "Unit 11",
-            desc: "The living room in Unit 11 is dimly lit, the only source of light being the glow of a few candles. A small cat is curled on the leather couch, and raises its head to look at you. Its owner is nowhere to be seen.",
-            doors: vec![Door {
-                target: RoomID(2),
-                direction: "south",
-                msg_on_open: Some("You step back into the hallway, and the door shuts softly behind you."),
-                ..Default::default()
-            }],
-            inventory: Inventory::default(),
-            characters: vec![Character {
-                name: "cat",
-                desc: "A medium-haired calico cat. It blinks slowly in your direction. You feel a bit silly, but you have the urge to talk to it.",
-                inventory: Inventory {
-                    objects: vec![Object {
-                        name: "sugar",
-                        desc: "Small delicate sugar cubes. Each individual granule seems to shimmer and strangely reflect the light.",
-                        inventory: vec![],
-                        can_take: true,
-                        ..Default::default()
-                    }],
-                },
-                on_talk: "You ask the cat if it can talk. It stares at you for a while, and just as you were about to give up, you hear it speak. `Hi, I suppose you're here for some sugar? Why don't you just ASK?`",
-                on_talk_again: "The cat seems to be preoccupied with trying to catch its own tail. You think it's best not to bother it.",
-                on_ask: "The cat thinks for a moment. `I was saving this sugar for a special moment, but I guess this is as good as any.` The cat takes out some sugar cubes. `Good luck with your tea!`",
-                has_interacted: false,
-            }],
+    // machine-readable mode: no music, no intro, just JSON lines
+    if options.format == Format::Jsonl {
+        let result = match &options.script {
+            Some(path) => {
+                let script = File::open(path)
+                    .unwrap_or_else(|e| fail(&format!("{}: {}", path.display(), e)));
+                protocol::run(&mut game, BufReader::new(script), io::stdout())
+            }
+            None => protocol::run(&mut game, io::stdin().lock(), io::stdout()),
+        };
+        result.unwrap();
+        return;
+    }
+
+    let script = options.script.as_ref().map(|path| {
+        let text = fs::read_to_string(path)
+            .unwrap_or_else(|e| fail(&format!("{}: {}", path.display(), e)));
+        text.lines()
+            .map(|line| line.trim().to_owned())
+            .filter(|line| !line.is_empty())
+            .collect()
+    });
+    let mut input = Input::new(script);
+    let mut session = Session {
+        audio: if options.audio {
+            open_audio()
+        } else {
+            Audio::silent()
         },
-    ]
+        world: world.clone(),
+        save_file: options
+            .load
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(SAVE_FILE)),
+        scenes: vec![],
+    };
+    session.audio.update(&game);
+
+    // start game
+    if save.is_some() {
+        println!("[Game restored.]");
+    } else if let Some(intro) = &game.cutscenes.intro {
+        intro.play(
+            &mut Stage::new(&mut session.audio, &mut input, &options),
+            options.speed,
+        );
+    }
+
+    #[cfg(feature = "tui")]
+    let mut tui = Tui::new(game.state.time_limit);
+    if !options.tui {
+        println!("{}", game.describe_room());
+    }
+    loop {
+        if options.tui {
+            // the TUI hands back control once the game ends, or if the player leaves
+            #[cfg(feature = "tui")]
+            tui.run(&mut game, |game, line| {
+                let mut messages = session.play(game, line);
+                messages.extend(session.scenes.drain(..).map(|scene| scene.text()));
+                messages
+            })
+            .unwrap();
+            if game.ending().is_none() {
+                break;
+            }
+        } else {
+            let line = match input.read(&game) {
+                Some(line) => line,
+                None => break,
+            };
+            for msg in session.play(&mut game, &line) {
+                println!("{}", msg);
+            }
+            for scene in mem::take(&mut session.scenes) {
+                scene.play(
+                    &mut Stage::new(&mut session.audio, &mut input, &options),
+                    options.speed,
+                );
+            }
+        }
+
+        // handle win/lose conditions
+        let ending = match game.ending() {
+            None => continue,
+            Some(ending) => ending,
+        };
+        let scene = match ending {
+            Ending::Won => &game.cutscenes.won,
+            Ending::Lost => &game.cutscenes.lost,
+        };
+        if let Some(scene) = scene {
+            scene.play(
+                &mut Stage::new(&mut session.audio, &mut input, &options),
+                options.speed,
+            );
+        }
+        if game.can_undo_ending(ending) && offer_undo(&mut input, &mut game, &mut session) {
+            continue;
+        }
+        break;
+    }
 }
 
-fn update(player: &mut Player, rooms: &mut [Room], state: &mut GameState) {
-    // check time-based events
-    let duration = state.start_time.elapsed().as_secs();
-    if duration > DOOR_CLOSED_LENGTH {
-        rooms[2]
-            .doors
-            .iter_mut()
-            .find(|door| door.target == RoomID(3))
-            .unwrap()
-            .is_open = true;
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
+
+/// Plays through the speakers if the build and the machine allow it, and stays quiet otherwise
+fn open_audio() -> Audio {
+    #[cfg(feature = "audio")]
+    {
+        if let Some(backend) = RodioBackend::try_default() {
+            return Audio::new(Box::new(backend));
+        }
+        println!("Music can't be played right now, sorry!");
     }
-    if duration >= GAME_LENGTH {
-        state.player_lost = true;
+    Audio::silent()
+}
+
+/// Gives the player a chance to take back the move that ended the game
+fn offer_undo(input: &mut Input, game: &mut Game, session: &mut Session) -> bool {
+    println!();
+    println!("Type UNDO to take back your last move, or press Enter to quit.");
+    let wants_undo = matches!(input.read(game), Some(line) if line.eq_ignore_ascii_case("undo"));
+    if wants_undo {
+        for msg in session.play(game, "undo") {
+            println!("{}", msg);
+        }
     }
+    wants_undo
+}
 
-    let mug = player.find_object_mut("mug");
-    if let Some(mug) = mug {
-        // check brew time on tea
-        if let Some(time) = state.tea_time {
-            if time.elapsed().as_secs() > TEA_BREW_LENGTH
-                && mug.inventory.contains(&"hot water".to_string())
-                && mug.inventory.contains(&"tea bag".to_string())
-            {
-                for object_name in &["hot water", "tea bag"] {
-                    let index = mug.inventory.iter().position(|o| o == object_name);
-                    if let Some(i) = index {
-                        mug.inventory.remove(i);
-                    }
-                    mug.inventory.push("brewed tea".to_string());
-                    mug.name = "brewed tea";
-                }
-                println!("Your tea is brewed, but you would really like some sugar. Maybe your neighbor in Unit 11 has some...")
+/// What this program handles itself before the world sees a line: music, volume and saving
+struct Session {
+    audio: Audio,
+    /// the world file being played, remembered in saves
+    world: Option<PathBuf>,
+    save_file: PathBuf,
+    /// cutscenes the world cued on the last turn, waiting to be played
+    scenes: Vec<Cutscene>,
+}
+
+impl Session {
+    fn play(&mut self, game: &mut Game, line: &str) -> Vec<String> {
+        if let Some(reply) = self.audio.command(line, game) {
+            return vec![reply];
+        }
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            [verb] | [verb, _] if verb.eq_ignore_ascii_case("save") => {
+                let path = words.get(1).map_or(self.save_file.as_path(), Path::new);
+                let world = self.world.as_ref().map(|path| path.display().to_string());
+                let reply = match Save::of(game, world).write(path) {
+                    Ok(()) => format!("Game saved to {}.", path.display()),
+                    Err(e) => format!("Couldn't save the game: {}", e),
+                };
+                vec![reply]
+            }
+            _ => {
+                let messages = game.step(line);
+                self.audio.update(game);
+                self.scenes = game.cued_scenes().into_iter().cloned().collect();
+                messages
             }
         }
     }
+}
+
+/// Plays cutscenes in the terminal. Enter skips the rest of a scene.
+struct Stage<'a> {
+    audio: &'a mut Audio,
+    input: &'a mut Input,
+    delay: bool,
+}
 
-    let brewed_tea = player.find_object_mut("brewed tea");
-    if let Some(tea) = brewed_tea {
-        // check win condition
-        if tea.inventory.contains(&"sugar".to_owned()) {
-            state.player_won = true;
+impl<'a> Stage<'a> {
+    fn new(audio: &'a mut Audio, input: &'a mut Input, options: &Options) -> Self {
+        Stage {
+            audio,
+            input,
+            delay: options.delay,
         }
     }
 }
 
-fn main() {
-    // play audio with rodio
-    let (_stream, stream_handle) = rodio::OutputStream::try_default().unwrap();
-    let file = File::open("music.mp3").unwrap();
-    let source = rodio::Decoder::new(BufReader::new(file)).unwrap();
-    let result = stream_handle.play_raw(source.convert_samples());
-    if result.is_err() {
-        println!("Music can't be played right now, sorry!")
+impl Renderer for Stage<'_> {
+    fn text(&mut self, line: &str) {
+        println!("{}", line);
     }
 
-    // get world
-    let mut rooms = get_rooms();
-    let mut player = Player {
-        name: "me",
-        desc: "a person",
-        at: RoomID(0),
-        inventory: Inventory::default(),
-    };
-
-    // set game state
-    let mut state = GameState {
-        player_won: false,
-        player_lost: false,
-        start_time: Instant::now(),
-        tea_time: None,
-    };
+    fn sound(&mut self, sound: &'static str) {
+        self.audio.play_once(sound);
+    }
 
-    let mut input = String::new();
+    fn pause(&mut self, duration: Duration) -> bool {
+        self.delay && self.input.wait(Some(duration))
+    }
 
-    // start game
-    println!("============================");
-    println!("Tea Time");
-    println!("============================");
-    thread::sleep(time::Duration::from_secs(3));
-    println!();
-    println!("You slowly open your eyes.");
-    println!();
-    thread::sleep(time::Duration::from_secs(5));
-    println!();
-    println!("You've fallen asleep on your couch. You blink groggily as your eyes adjust to the darkness. Wait... it's dark outside? What time is it...");
-    println!();
-    thread::sleep(time::Duration::from_secs(5));
-    println!();
-    println!("You drag yourself up and turn on the lights. You're still really sleepy. A warm cup of tea sounds like the best thing in the world right now. You only have until 12am...");
-    println!();
-    thread::sleep(time::Duration::from_secs(5));
+    fn wait_for_key(&mut self) -> bool {
+        if !self.delay {
+            return false;
+        }
+        println!("[Press Enter to continue.]");
+        self.input.wait(None);
+        false
+    }
+}
 
-    'game: loop {
-        let here = &rooms[player.at.0];
-        let border = get_room_name_border();
-        println!("{}\n{}\n{}\n{}", border, here.name, border, here.desc);
-        loop {
-            print!("\n> ");
-            io::stdout().flush().unwrap();
+/// Where the player's commands come from: a script, a line editor when playing
+/// in a terminal, or plain lines from stdin otherwise
+struct Input {
+    line: String,
+    script: Option<Vec<String>>,
+    #[cfg(feature = "line-editor")]
+    editor: Option<LineEditor>,
+}
 
-            let trimmed_input = get_trimmed_input(&mut input);
-            let parsed_input = Parser::parse(&trimmed_input);
+impl Input {
+    fn new(script: Option<Vec<String>>) -> Self {
+        Input {
+            line: String::new(),
+            #[cfg(feature = "line-editor")]
+            editor: match script {
+                Some(_) => None,
+                None => LineEditor::for_terminal(
+                    env::var_os("HOME").map(|home| Path::new(&home).join(HISTORY_FILE)),
+                ),
+            },
+            script,
+        }
+    }
 
-            match parsed_input {
-                Ok(command) => {
-                    let result = CommandHandler::handle_command(
-                        command,
-                        &mut player,
-                        &mut rooms,
-                        &mut state,
-                    );
-                    println!("{}", result.message)
-                }
-                Err(msg) => println!("{}", msg),
+    /// Waits for `timeout`, or until the player presses Enter if there's no timeout.
+    /// Returns whether Enter cut the wait short.
+    fn wait(&mut self, timeout: Option<Duration>) -> bool {
+        #[cfg(feature = "line-editor")]
+        {
+            if self.editor.is_some() {
+                return line_editor::wait_for_enter(timeout).unwrap();
             }
+        }
+        match timeout {
+            Some(timeout) => thread::sleep(timeout),
+            // with no one at a terminal, there's nobody to wait for
+            None if self.script.is_none() && io::stdin().is_terminal() => {
+                get_trimmed_input(&mut self.line);
+            }
+            None => {}
+        }
+        false
+    }
 
-            // update game state and events
-            update(&mut player, &mut rooms, &mut state);
-
-            // handle win/lose conditions
-            if state.player_won {
-                thread::sleep(time::Duration::from_secs(3));
-                println!();
-                println!("You look down at your tea and watch the sugar slowly disappear. You walk to your couch and sit down, waiting for it to cool down a bit.");
-                println!();
-                thread::sleep(time::Duration::from_secs(5));
-                println!();
-                println!("You take a sip of your tea -- it's perfectly bittersweet and fills you with warmth. You glance at your watch. It reads 12:00am.");
-                println!();
-                thread::sleep(time::Duration::from_secs(5));
-                println!();
-                println!("You close your eyes and listen to the gentle rainfall. Not long after, you drift off into sleep.");
-                println!();
-                thread::sleep(time::Duration::from_secs(5));
-                println!("============================");
-                println!("THE END");
-                println!("============================");
-                break 'game;
-            } else if state.player_lost {
-                thread::sleep(time::Duration::from_secs(3));
-                println!();
-                println!("You get the sudden urge to stop in your tracks. Far away, a gong starts to ring.");
-                println!();
-                thread::sleep(time::Duration::from_secs(5));
-                println!();
-                println!("You glance at your watch. It reads 11:59pm. With each tick of the second hand, the gong sounds closer and closer.");
-                println!();
-                thread::sleep(time::Duration::from_secs(5));
-                println!();
-                println!("Finally, all hands of the watch meet. The ringing in your ears is unbearably loud. After what seems like an eternity, everything fades to black.");
+    /// Prompts for and reads one command. Returns `None` if the player asked to
+    /// leave, or the script or stdin has run out.
+    fn read(&mut self, game: &Game) -> Option<String> {
+        if let Some(script) = &mut self.script {
+            if script.is_empty() {
+                return None;
+            }
+            let line = script.remove(0);
+            println!("\n> {}", line);
+            return Some(line);
+        }
+        #[cfg(feature = "line-editor")]
+        {
+            if let Some(editor) = &mut self.editor {
                 println!();
-                thread::sleep(time::Duration::from_secs(5));
-                println!("============================");
-                println!("THE END");
-                println!("============================");
-                break 'game;
+                return editor.read_line("> ", |line| complete(game, line)).unwrap();
             }
         }
+        #[cfg(not(feature = "line-editor"))]
+        let _ = game;
+        print!("\n> ");
+        io::stdout().flush().unwrap();
+        get_trimmed_input(&mut self.line).map(str::to_owned)
     }
 }
@@ -0,0 +1,34 @@
+use std::env;
+use std::process;
+use text_engine::server::Server;
+use text_engine::worlds::tea_time;
+
+const DEFAULT_PORT: u16 = 4000;
+
+/// Lets several people play on one machine: `telnet localhost 4000`.
+/// With `--shared`, everyone plays together in the same world.
+/// Usage: server [--shared] [port]
+fn main() {
+    let mut shared = false;
+    let mut port = DEFAULT_PORT;
+    for arg in env::args().skip(1) {
+        if arg == "--shared" {
+            shared = true;
+        } else {
+            port = arg.parse().unwrap_or_else(|_| {
+                eprintln!("usage: server [--shared] [port]");
+                process::exit(2);
+            });
+        }
+    }
+    let server = Server::bind(("127.0.0.1", port), tea_time::new_game).unwrap_or_else(|e| {
+        eprintln!("can't listen on port {}: {}", port, e);
+        process::exit(1);
+    });
+    println!("Listening on {}", server.local_addr().unwrap());
+    if shared {
+        server.run_shared().unwrap();
+    } else {
+        server.run().unwrap();
+    }
+}
@@ -0,0 +1,69 @@
+use std::path::PathBuf;
+use std::time::Duration;
+use std::{env, process};
+use text_engine::clock::Clock;
+use text_engine::solver::Solver;
+use text_engine::util::watch_time;
+use text_engine::worlds::{file, tea_time};
+
+const USAGE: &str = "usage: solve [--world <file>] [wait steps in minutes...]";
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1);
+}
+
+/// Proves that a world can be won, and prints the shortest way to do it.
+/// Searches Tea Time unless given a world file.
+fn main() {
+    let mut world = None;
+    let mut wait_steps = vec![];
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--world" => match args.next() {
+                Some(path) => world = Some(PathBuf::from(path)),
+                None => fail(USAGE),
+            },
+            _ => match arg.parse() {
+                Ok(minutes) => wait_steps.push(minutes),
+                Err(_) => fail(USAGE),
+            },
+        }
+    }
+
+    let mut solver = match world {
+        // the file is read once, and every search starts from a copy of it
+        Some(path) => {
+            let game = file::load(&path, Clock::manual()).unwrap_or_else(|e| fail(&e));
+            Solver::new(move || game.clone())
+        }
+        None => Solver::new(|| tea_time::new_game(Clock::manual())),
+    };
+    if !wait_steps.is_empty() {
+        solver.wait_steps = wait_steps;
+    }
+
+    let report = solver.solve();
+    println!(
+        "explored {} states{}",
+        report.states,
+        if report.complete { "" } else { " (incomplete)" }
+    );
+    match report.shortest_win {
+        Some(path) => {
+            println!("shortest win ({} commands):", path.len());
+            for action in path {
+                println!("  > {}", action);
+            }
+        }
+        None => println!("no ending reachable!"),
+    }
+    if let Some(time) = report.earliest_win {
+        println!("earliest win at {}", watch_time(Duration::from_secs(time)));
+    }
+    println!("{} dead-end states", report.dead_ends);
+    for path in report.points_of_no_return.iter().take(5) {
+        println!("  no return after: {}", path.join(", "));
+    }
+}
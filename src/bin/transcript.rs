@@ -0,0 +1,38 @@
+use std::env;
+use std::path::Path;
+use std::process;
+use text_engine::clock::Clock;
+use text_engine::game::Game;
+use text_engine::transcript::check_file;
+use text_engine::worlds::tea_time;
+
+fn new_game() -> Game {
+    tea_time::new_game(Clock::manual())
+}
+
+/// Replays golden transcripts against the engine.
+/// Usage: transcript [--bless] <file>...
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let bless = args.iter().any(|arg| arg == "--bless");
+    let files: Vec<&String> = args.iter().filter(|arg| !arg.starts_with("--")).collect();
+    if files.is_empty() {
+        eprintln!("usage: transcript [--bless] <file>...");
+        process::exit(2);
+    }
+
+    let mut failed = false;
+    for file in files {
+        match check_file(Path::new(file), new_game, bless) {
+            Ok(()) if bless => println!("blessed {}", file),
+            Ok(()) => println!("ok {}", file),
+            Err(msg) => {
+                failed = true;
+                println!("FAILED {}", msg);
+            }
+        }
+    }
+    if failed {
+        process::exit(1);
+    }
+}
@@ -1,26 +1,57 @@
-use crate::entity::Object;
+use crate::description::{render, Variant};
+use crate::dialogue::Dialogue;
+use crate::game_state::GameState;
 use crate::inventory::Inventory;
+use crate::player::Player;
+use crate::room::Room;
+use crate::schedule::Schedule;
+use crate::world::CharacterID;
 
+#[derive(Clone)]
 pub struct Character {
+    pub id: CharacterID,
     pub name: &'static str,
     pub desc: &'static str,
+    /// see `Variant`
+    pub variants: Vec<Variant>,
     pub inventory: Inventory,
-    pub on_talk: &'static str,
-    pub on_talk_again: &'static str,
-    pub on_ask: &'static str,
-    pub has_interacted: bool,
+    pub dialogue: Dialogue,
+    pub trades: Vec<Trade>,
+    pub msg_on_refuse: Option<&'static str>,
+    pub schedule: Schedule,
+    /// whether the character agrees to follow the player when asked
+    pub will_follow: bool,
+    pub is_following: bool,
+    pub msg_on_follow: Option<&'static str>,
+}
+
+/// Something a character will accept from the player, and what they hand over in return
+#[derive(Debug, Clone)]
+pub struct Trade {
+    pub wants: &'static str,
+    pub gives: Option<&'static str>,
+    pub msg_on_trade: Option<&'static str>,
 }
 
 impl Character {
-    pub fn has(&self, object_name: &str) -> bool {
-        self.inventory.contains(object_name)
+    pub fn describe(&self, player: &Player, rooms: &[Room], state: &GameState) -> String {
+        let contents: Vec<String> = self
+            .inventory
+            .objects
+            .iter()
+            .map(|o| o.name.to_owned())
+            .collect();
+        render(self.desc, &self.variants, &contents, player, rooms, state)
     }
 
-    pub fn find_object_mut(&mut self, name: &str) -> Option<&mut Object> {
-        self.inventory.find_object_mut(name)
+    pub fn has(&self, object_name: &str) -> bool {
+        self.inventory.contains(object_name)
     }
 
-    pub fn give_object(&mut self, name: &str) -> Option<Object> {
-        self.inventory.remove(name)
+    /// Finds a trade for the object that the character is still able to honour
+    pub fn find_trade(&self, object_name: &str) -> Option<&Trade> {
+        self.trades.iter().find(|trade| {
+            trade.wants == object_name && trade.gives.iter().all(|reward| self.has(reward))
+        })
     }
 }
@@ -0,0 +1,128 @@
+use crate::clock::Clock;
+use std::path::PathBuf;
+use std::time::Duration;
+
+pub const USAGE: &str = "usage: game [options]
+  --world <file>           play a world file instead of Tea Time
+  --load <save>            pick up a saved game
+  --no-audio               play without music or sound effects
+  --no-delay               skip the pauses in cutscenes
+  --speed <x>              play cutscenes x times as fast
+  --clock realtime|turns   let time pass in real time, or one minute per turn
+  --seed <n>               seed for anything random in the world
+  --script <file>          play the commands in a file, one per line
+                           (with --format jsonl, one JSON request per line)
+  --format text|jsonl      write prose, or one JSON object per line
+  --tui                    play full-screen
+  --debug                  allow the @ commands for testing a world (try @help)";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockKind {
+    Realtime,
+    Turns,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Text,
+    Jsonl,
+}
+
+/// How the player asked the game to run
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    pub world: Option<PathBuf>,
+    pub load: Option<PathBuf>,
+    pub audio: bool,
+    pub delay: bool,
+    /// how many times faster than written cutscenes play
+    pub speed: f32,
+    pub clock: ClockKind,
+    pub seed: Option<u64>,
+    pub script: Option<PathBuf>,
+    pub format: Format,
+    pub tui: bool,
+    pub debug: bool,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            world: None,
+            load: None,
+            audio: true,
+            delay: true,
+            speed: 1.0,
+            clock: ClockKind::Realtime,
+            seed: None,
+            script: None,
+            format: Format::Text,
+            tui: false,
+            debug: false,
+        }
+    }
+}
+
+impl Options {
+    /// Reads the arguments after the program name. Errors say which argument was wrong.
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Options, String> {
+        let mut options = Options::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+            match arg.as_str() {
+                "--world" => options.world = Some(PathBuf::from(value()?)),
+                "--load" => options.load = Some(PathBuf::from(value()?)),
+                "--no-audio" => options.audio = false,
+                "--no-delay" => options.delay = false,
+                "--speed" => {
+                    let speed = value()?;
+                    options.speed = match speed.parse::<f32>() {
+                        Ok(speed) if speed > 0.0 && speed.is_finite() => speed,
+                        _ => {
+                            return Err(format!("`{}` isn't a speed; use a number above 0", speed))
+                        }
+                    };
+                }
+                "--clock" => {
+                    options.clock = match value()?.as_str() {
+                        "realtime" => ClockKind::Realtime,
+                        "turns" => ClockKind::Turns,
+                        other => return Err(format!("unknown clock `{}`", other)),
+                    }
+                }
+                "--seed" => {
+                    let seed = value()?;
+                    options.seed = Some(
+                        seed.parse()
+                            .map_err(|_| format!("`{}` isn't a seed; use a whole number", seed))?,
+                    );
+                }
+                "--script" => options.script = Some(PathBuf::from(value()?)),
+                // --protocol is what --format was first called
+                "--format" | "--protocol" => {
+                    options.format = match value()?.as_str() {
+                        "text" => Format::Text,
+                        "jsonl" => Format::Jsonl,
+                        other => return Err(format!("unknown format `{}`", other)),
+                    }
+                }
+                "--tui" => options.tui = true,
+                "--debug" => options.debug = true,
+                other => return Err(format!("unknown option `{}`", other)),
+            }
+        }
+        if options.tui && (options.script.is_some() || options.format == Format::Jsonl) {
+            return Err("--tui is for playing by hand; it can't take a script or JSON".to_owned());
+        }
+        Ok(options)
+    }
+
+    /// A fresh clock of the kind asked for. Turns move the clock one minute on the watch.
+    pub fn clock(&self) -> Clock {
+        match self.clock {
+            ClockKind::Realtime => Clock::realtime(),
+            ClockKind::Turns => Clock::turns(Duration::from_secs(1)),
+        }
+    }
+}
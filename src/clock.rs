@@ -0,0 +1,90 @@
+use std::time::{Duration, Instant};
+
+/// Game time, measured in seconds since the start of the game.
+/// One second of game time is one minute on the player's watch.
+#[derive(Debug, Clone)]
+pub enum Clock {
+    /// follows the wall clock, plus any time the player has waited
+    Realtime { start: Instant, skipped: Duration },
+    /// only moves when advanced, for scripts and tests
+    Manual { elapsed: Duration },
+    /// moves forward by `per_turn` every turn the player takes
+    Turns {
+        elapsed: Duration,
+        per_turn: Duration,
+    },
+}
+
+impl Clock {
+    pub fn realtime() -> Self {
+        Clock::Realtime {
+            start: Instant::now(),
+            skipped: Duration::from_secs(0),
+        }
+    }
+
+    pub fn manual() -> Self {
+        Clock::Manual {
+            elapsed: Duration::from_secs(0),
+        }
+    }
+
+    pub fn turns(per_turn: Duration) -> Self {
+        Clock::Turns {
+            elapsed: Duration::from_secs(0),
+            per_turn,
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        match self {
+            Clock::Realtime { start, skipped } => start.elapsed() + *skipped,
+            Clock::Manual { elapsed } | Clock::Turns { elapsed, .. } => *elapsed,
+        }
+    }
+
+    pub fn elapsed_secs(&self) -> u64 {
+        self.elapsed().as_secs()
+    }
+
+    pub fn advance(&mut self, by: Duration) {
+        match self {
+            Clock::Realtime { skipped, .. } => *skipped += by,
+            Clock::Manual { elapsed } | Clock::Turns { elapsed, .. } => *elapsed += by,
+        }
+    }
+
+    /// Sets the clock to show `elapsed`, leaving it running however it was running
+    pub fn set_elapsed(&mut self, to: Duration) {
+        match self {
+            Clock::Realtime { start, skipped } => {
+                *start = Instant::now();
+                *skipped = to;
+            }
+            Clock::Manual { elapsed } | Clock::Turns { elapsed, .. } => *elapsed = to,
+        }
+    }
+
+    /// Called once per turn. Only a turn-based clock cares.
+    pub fn tick(&mut self) {
+        if let Clock::Turns { elapsed, per_turn } = self {
+            *elapsed += *per_turn;
+        }
+    }
+
+    /// Lets a turn's worth of effort pass: a turn on a turn-based clock, or `by` on any other
+    pub fn spend(&mut self, by: Duration) {
+        match self {
+            Clock::Turns { .. } => self.tick(),
+            _ => self.advance(by),
+        }
+    }
+
+    /// Stops the clock from counting `by` worth of wall-clock time.
+    /// Other clocks don't follow the wall clock, so they are left alone.
+    pub fn pause_for(&mut self, by: Duration) {
+        if let Clock::Realtime { start, .. } = self {
+            *start += by;
+        }
+    }
+}
@@ -1,36 +1,47 @@
+use crate::world::Entity;
+
 #[derive(Debug, Clone)]
 pub struct Command {
     pub verb: Option<String>,
     pub obj: Option<String>,
     pub prep: Option<String>,
     pub obj_prep: Option<String>,
+    /// what `obj` and `obj_prep` turned out to name, once the parser has resolved them
+    pub target: Option<Entity>,
+    pub target_prep: Option<Entity>,
 }
 
 pub struct CommandResult {
     pub message: String,
+    /// whether the command did what was asked, rather than being turned down
+    pub success: bool,
 }
 
 impl CommandResult {
     pub fn didnt_understand(word: String) -> Self {
         CommandResult {
             message: format!("I don't know what {} means.", word),
+            success: false,
         }
     }
 
     pub fn no_object(name: String) -> Self {
         CommandResult {
             message: format!("There is no {} here.", name),
+            success: false,
         }
     }
 
     pub fn cant_do_that(verb: String) -> Self {
         CommandResult {
             message: format!("You can't {} that.", verb),
+            success: false,
         }
     }
     pub fn doesnt_have_that(character_name: String) -> Self {
         CommandResult {
             message: format!("The {} doesn't have that.", character_name),
+            success: false,
         }
     }
 }
@@ -1,131 +1,418 @@
 use crate::command::Command;
 use crate::command::CommandResult;
+use crate::dialogue::NodeID;
 use crate::entity::Object;
-use crate::game_state::GameState;
+use crate::game_state::{GameState, Verbosity};
+use crate::map;
 use crate::player::Player;
-use crate::room::Room;
-use std::time::Instant;
+use crate::room::{Room, RoomID};
+use crate::util::watch_time;
+use crate::vars::Value;
+use crate::world::{CharacterID, Entity, Location, ObjectID, World};
+use std::time::Duration;
+
+/// What a verb does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Go,
+    Examine,
+    Take,
+    Drop,
+    Inventory,
+    Put,
+    Use,
+    Wait,
+    Talk,
+    Ask,
+    Give,
+    Tell,
+    Map,
+    Verbosity(Verbosity),
+    // the rest act on the history or take several turns, so `Game::step` plays them
+    Undo,
+    Redo,
+    Travel,
+    Return,
+}
+
+pub struct Verb {
+    pub name: &'static str,
+    /// other words the player can use for it
+    pub aliases: &'static [&'static str],
+    pub action: Action,
+}
+
+impl Verb {
+    /// Whether handle_command plays the verb, as opposed to `Game::step`
+    pub fn takes_one_turn(&self) -> bool {
+        !matches!(
+            self.action,
+            Action::Undo | Action::Redo | Action::Travel | Action::Return
+        )
+    }
+}
+
+const fn verb(name: &'static str, aliases: &'static [&'static str], action: Action) -> Verb {
+    Verb {
+        name,
+        aliases,
+        action,
+    }
+}
+
+/// Every verb the game understands. Completion, aliases and handle_command all go by this.
+pub const VERBS: [Verb; 23] = [
+    verb("north", &["n"], Action::Go),
+    verb("south", &["s"], Action::Go),
+    verb("east", &["e"], Action::Go),
+    verb("west", &["w"], Action::Go),
+    verb("examine", &["x", "look"], Action::Examine),
+    verb("take", &["pickup", "get"], Action::Take),
+    verb("drop", &["discard"], Action::Drop),
+    verb("inventory", &["i", "items"], Action::Inventory),
+    verb("put", &["place"], Action::Put),
+    verb("use", &[], Action::Use),
+    verb("wait", &["z"], Action::Wait),
+    verb("talk", &[], Action::Talk),
+    verb("ask", &[], Action::Ask),
+    verb("give", &["offer"], Action::Give),
+    verb("tell", &[], Action::Tell),
+    verb("map", &[], Action::Map),
+    verb("brief", &[], Action::Verbosity(Verbosity::Brief)),
+    verb("verbose", &[], Action::Verbosity(Verbosity::Verbose)),
+    verb("superbrief", &[], Action::Verbosity(Verbosity::Superbrief)),
+    verb("undo", &[], Action::Undo),
+    verb("redo", &[], Action::Redo),
+    verb("go to", &["travel to"], Action::Travel),
+    verb("return", &["go back"], Action::Return),
+];
+
+/// The verb a word stands for, by its name or one of its aliases
+pub fn find_verb(word: &str) -> Option<&'static Verb> {
+    VERBS
+        .iter()
+        .find(|verb| verb.name == word || verb.aliases.contains(&word))
+}
+
+/// The verb a line starts with, and the rest of the line. Some verbs are more than
+/// one word, like `go to`, so the longest match wins.
+pub fn split_verb(line: &str) -> Option<(&'static Verb, &str)> {
+    let line = line.trim();
+    VERBS
+        .iter()
+        .flat_map(|verb| {
+            std::iter::once(&verb.name)
+                .chain(verb.aliases)
+                .map(move |word| (verb, *word))
+        })
+        .filter(|(_, word)| {
+            line.strip_prefix(word)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(' '))
+        })
+        .max_by_key(|(_, word)| word.len())
+        .map(|(verb, word)| (verb, line[word.len()..].trim()))
+}
+
+/// The full name of a verb, however the player typed it
+pub fn full_verb(verb: &str) -> &str {
+    find_verb(verb).map_or(verb, |verb| verb.name)
+}
 
 pub struct CommandHandler;
 
 impl CommandHandler {
+    /// Plays a command, finding what it's about through `world` and keeping it up to date
     pub fn handle_command(
         command: Command,
         player: &mut Player,
         rooms: &mut [Room],
+        world: &mut World,
         state: &mut GameState,
     ) -> CommandResult {
         let cmd = command.clone();
         let verb = command.verb.unwrap_or_default();
-        match verb.as_str() {
-            "north" | "n" | "south" | "s" | "east" | "e" | "west" | "w" => {
-                CommandHandler::handle_go(&verb, player, rooms)
+        // numbered choices only make sense right after a line of dialogue
+        if verb.parse::<usize>().is_err() {
+            state.talking_to = None;
+        }
+        match find_verb(&verb).map(|known| known.action) {
+            Some(Action::Go) => {
+                CommandHandler::handle_go(full_verb(&verb), player, rooms, world, state)
+            }
+            Some(Action::Examine) => {
+                CommandHandler::handle_examine(cmd, player, rooms, world, state)
+            }
+            Some(Action::Take) => CommandHandler::handle_take(cmd, player, rooms, world),
+            Some(Action::Drop) => CommandHandler::handle_drop(cmd, player, rooms, world),
+            Some(Action::Inventory) => CommandHandler::handle_inventory(player),
+            Some(Action::Put) => CommandHandler::handle_put(cmd, player, rooms, world, state),
+            Some(Action::Use) => CommandHandler::handle_use(cmd, player, rooms, world, state),
+            Some(Action::Wait) => CommandHandler::handle_wait(cmd, state),
+            Some(Action::Talk) => CommandHandler::handle_talk(cmd, player, rooms, world, state),
+            Some(Action::Ask) => CommandHandler::handle_ask(cmd, player, rooms, world, state),
+            Some(Action::Give) => CommandHandler::handle_give(cmd, player, rooms, world),
+            Some(Action::Tell) => CommandHandler::handle_tell(cmd, rooms, world),
+            Some(Action::Map) => CommandResult {
+                message: map::render(player, rooms),
+                success: true,
+            },
+            Some(Action::Verbosity(verbosity)) => {
+                CommandHandler::handle_verbosity(verbosity, state)
+            }
+            // world files can have exits in any direction, like up or out
+            _ if world.door_towards(player.at, &verb).is_some() => {
+                CommandHandler::handle_go(&verb, player, rooms, world, state)
+            }
+            _ if verb.parse::<usize>().is_ok() => {
+                CommandHandler::handle_choice(verb.parse().unwrap(), player, rooms, world, state)
             }
-            "examine" | "x" | "look" => CommandHandler::handle_examine(cmd, player, rooms),
-            "take" | "pickup" | "get" => CommandHandler::handle_take(cmd, player, rooms),
-            "inventory" | "i" | "items" => CommandHandler::handle_inventory(player),
-            "put" | "place" => CommandHandler::handle_put(cmd, player, rooms, &mut state.tea_time),
-            "use" => CommandHandler::handle_use(cmd, player, rooms, state.start_time),
-            "talk" => CommandHandler::handle_talk(cmd, player, rooms),
-            "ask" => CommandHandler::handle_ask(cmd, player, rooms),
             _ => CommandResult::didnt_understand(verb),
         }
     }
 
-    fn handle_go(direction: &str, player: &mut Player, rooms: &mut [Room]) -> CommandResult {
-        let door = player.get_curr_room(rooms).get_door(direction);
+    fn handle_go(
+        direction: &str,
+        player: &mut Player,
+        rooms: &mut [Room],
+        world: &mut World,
+        state: &mut GameState,
+    ) -> CommandResult {
+        let door = world
+            .door_towards(player.at, direction)
+            .and_then(|id| world.door(id, rooms));
         match door {
             Some(door) => {
-                if door.is_open {
+                let guard_holds = door
+                    .guard
+                    .as_ref()
+                    .is_none_or(|guard| guard.holds(player, rooms, state));
+                if door.is_open && guard_holds {
+                    let from = player.at;
+                    let door_msg = door.msg_on_open.unwrap_or_default().to_owned();
+                    if let Some(sound) = door.sound_on_open {
+                        state.play_sound(sound);
+                    }
                     player.go(door.target);
+                    let follow_msgs = CommandHandler::move_followers(from, player.at, rooms, world);
                     let curr_room = player.get_curr_room(rooms);
-                    let room_name = curr_room.get_display_name();
-                    let door_msg = door.msg_on_open.unwrap_or_default().to_owned();
-                    let room_msg = curr_room.desc.to_owned();
-                    let msg = if door_msg.is_empty() {
-                        format!("{}\n{}", room_name, room_msg)
+                    let in_full = match state.verbosity {
+                        Verbosity::Verbose => true,
+                        Verbosity::Brief => player.visits(player.at) == 1,
+                        Verbosity::Superbrief => false,
+                    };
+                    let room_msg = if in_full {
+                        curr_room.describe(player, rooms, state)
                     } else {
-                        format!("{}\n{}\n{}", room_name, door_msg, room_msg)
+                        curr_room.details().trim_start().to_owned()
                     };
-                    CommandResult { message: msg }
+                    let mut lines = vec![curr_room.get_display_name(), door_msg, room_msg];
+                    lines.extend(follow_msgs);
+                    lines.retain(|line| !line.is_empty());
+                    CommandResult {
+                        message: lines.join("\n"),
+                        success: true,
+                    }
                 } else {
                     CommandResult {
                         message: door.msg_on_closed.unwrap_or_default().to_owned(),
+                        success: false,
                     }
                 }
             }
             None => CommandResult {
                 message: "You can't go that way.".to_owned(),
+                success: false,
             },
         }
     }
 
-    fn handle_examine(command: Command, player: &mut Player, rooms: &mut [Room]) -> CommandResult {
-        let curr_room = player.get_curr_room_mut(rooms);
+    fn handle_verbosity(verbosity: Verbosity, state: &mut GameState) -> CommandResult {
+        state.verbosity = verbosity;
+        let message = match verbosity {
+            Verbosity::Verbose => {
+                "Verbose mode: rooms are described in full every time you enter them."
+            }
+            Verbosity::Brief => {
+                "Brief mode: rooms are described in full the first time you enter them."
+            }
+            Verbosity::Superbrief => {
+                "Superbrief mode: rooms are never described in full. LOOK to see one."
+            }
+        };
+        CommandResult {
+            message: message.to_owned(),
+            success: true,
+        }
+    }
+
+    /// Brings any characters following the player along into the new room
+    fn move_followers(
+        from: RoomID,
+        to: RoomID,
+        rooms: &mut [Room],
+        world: &mut World,
+    ) -> Vec<String> {
+        let followers: Vec<(CharacterID, String)> = rooms[from.0]
+            .characters
+            .iter()
+            .filter(|character| character.is_following)
+            .map(|character| {
+                let msg = match character.msg_on_follow {
+                    Some(msg) => msg.to_owned(),
+                    None => format!("The {} follows you.", character.name),
+                };
+                (character.id, msg)
+            })
+            .collect();
+        followers
+            .into_iter()
+            .map(|(id, msg)| {
+                world.move_character(id, to, rooms);
+                msg
+            })
+            .collect()
+    }
+
+    fn handle_examine(
+        command: Command,
+        player: &mut Player,
+        rooms: &mut [Room],
+        world: &World,
+        state: &GameState,
+    ) -> CommandResult {
+        let here = player.at;
+        let object_id = command.target.and_then(Entity::object);
+        let character_id = command.target.and_then(Entity::character);
+        let object =
+            object_id.and_then(|id| Some((world.locate(id)?, world.object(id, player, rooms)?)));
         match command.obj {
             Some(object_name) => {
                 let name = object_name.as_str();
                 if name == "me" || name == "myself" {
                     CommandResult {
                         message: player.desc.to_owned(),
+                        success: true,
                     }
-                } else if let Some(object) = player.find_object(name) {
+                } else if let Some((Location::Player(_), object)) = object {
                     CommandResult {
-                        message: object.desc.to_owned(),
+                        message: object.describe(player, rooms, state),
+                        success: true,
                     }
-                } else if let Some(object) = curr_room.find_object(name).cloned() {
-                    let mut msg = object.desc.to_owned();
-                    if !object.inventory.is_empty() {
-                        for name in &object.inventory {
-                            curr_room.find_object_mut(name).unwrap().can_take = true;
-                            msg = format!(
-                                "{}\nYou can now take: {}.",
-                                object.desc.to_owned(),
-                                object.inventory.join(", ")
-                            );
+                } else if let Some((Location::Room(_), object)) = object {
+                    let mut msg = object.describe(player, rooms, state);
+                    let contents = object.inventory.clone();
+                    if !contents.is_empty() {
+                        for name in &contents {
+                            // contents that were used up no longer exist as objects
+                            let content = world
+                                .object_named(Location::Room(here), name)
+                                .and_then(|id| world.object_mut(id, player, rooms));
+                            if let Some(content) = content {
+                                content.can_take = true;
+                            }
                         }
+                        msg = format!("{}\nYou can now take: {}.", msg, contents.join(", "));
+                    }
+                    CommandResult {
+                        message: msg,
+                        success: true,
                     }
-                    CommandResult { message: msg }
-                } else if let Some(character) = curr_room.find_character(name) {
+                } else if let Some(character) =
+                    character_id.and_then(|id| world.character(id, rooms))
+                {
                     CommandResult {
-                        message: character.desc.to_owned(),
+                        message: character.describe(player, rooms, state),
+                        success: true,
                     }
                 } else {
                     CommandResult::cant_do_that("do".to_string())
                 }
             }
             None => CommandResult {
-                message: player.get_curr_room(rooms).desc.to_owned(),
+                message: player.get_curr_room(rooms).describe(player, rooms, state),
+                success: true,
             },
         }
     }
 
-    fn handle_take(command: Command, player: &mut Player, rooms: &mut [Room]) -> CommandResult {
-        let curr_room = player.get_curr_room_mut(rooms);
-        match command.obj {
+    /// The object a command is about, if it's in `at` and can be picked up
+    fn takeable(
+        command: &Command,
+        at: Location,
+        player: &Player,
+        rooms: &[Room],
+        world: &World,
+    ) -> Option<ObjectID> {
+        command
+            .target
+            .and_then(Entity::object)
+            .filter(|id| world.locate(*id) == Some(at))
+            .filter(|id| {
+                world
+                    .object(*id, player, rooms)
+                    .is_some_and(|object| object.can_take)
+            })
+    }
+
+    fn handle_take(
+        command: Command,
+        player: &mut Player,
+        rooms: &mut [Room],
+        world: &mut World,
+    ) -> CommandResult {
+        let here = Location::Room(player.at);
+        match &command.obj {
             Some(object_name) => {
-                if curr_room.has(&object_name) && curr_room.find_object(&object_name).is_some() {
-                    match curr_room.remove(&object_name) {
-                        Some(object) => {
-                            player.take_object(object);
-                            for obj in &mut curr_room.inventory.objects {
-                                let index = obj.inventory.iter().position(|o| *o == object_name);
-                                if let Some(i) = index {
-                                    obj.inventory.remove(i);
-                                }
-                            }
-                            CommandResult {
-                                message: format!("You take the {}.", object_name),
+                match CommandHandler::takeable(&command, here, player, rooms, world) {
+                    Some(id) => {
+                        world.move_object(id, Location::Player(player.id), player, rooms);
+                        for obj in &mut player.get_curr_room_mut(rooms).inventory.objects {
+                            let index = obj.inventory.iter().position(|o| o == object_name);
+                            if let Some(i) = index {
+                                obj.inventory.remove(i);
                             }
                         }
-                        None => CommandResult::cant_do_that("do".to_owned()),
+                        CommandResult {
+                            message: format!("You take the {}.", object_name),
+                            success: true,
+                        }
                     }
-                } else {
-                    CommandResult::cant_do_that("do".to_owned())
+                    None => CommandResult::cant_do_that("do".to_owned()),
                 }
             }
             None => CommandResult {
                 message: "You can't take nothing!".to_string(),
+                success: false,
+            },
+        }
+    }
+
+    fn handle_drop(
+        command: Command,
+        player: &mut Player,
+        rooms: &mut [Room],
+        world: &mut World,
+    ) -> CommandResult {
+        let carried = Location::Player(player.id);
+        match &command.obj {
+            Some(object_name) => {
+                match CommandHandler::takeable(&command, carried, player, rooms, world) {
+                    Some(id) => {
+                        world.move_object(id, Location::Room(player.at), player, rooms);
+                        CommandResult {
+                            message: format!("You drop the {}.", object_name),
+                            success: true,
+                        }
+                    }
+                    None => CommandResult {
+                        message: format!("You don't have the {}.", object_name),
+                        success: false,
+                    },
+                }
+            }
+            None => CommandResult {
+                message: "You can't drop nothing!".to_string(),
+                success: false,
             },
         }
     }
@@ -135,6 +422,7 @@ impl CommandHandler {
         if objects_str.is_empty() {
             CommandResult {
                 message: "Your pockets are empty.".to_string(),
+                success: true,
             }
         } else {
             CommandResult {
@@ -142,6 +430,7 @@ impl CommandHandler {
                     "Here are the contents of your pockets:\n\t- {}",
                     objects_str
                 ),
+                success: true,
             }
         }
     }
@@ -150,44 +439,54 @@ impl CommandHandler {
         command: Command,
         player: &mut Player,
         rooms: &mut [Room],
-        tea_time: &mut Option<Instant>,
+        world: &mut World,
+        state: &mut GameState,
     ) -> CommandResult {
         let cmd = command.clone();
-        let curr_room = player.get_curr_room_mut(rooms);
+        let carried = Location::Player(player.id);
+        let carried = CommandHandler::takeable(&command, carried, player, rooms, world);
         match command.obj {
             Some(object_name) => {
                 // if the player has the object
-                if player.has(object_name.as_str()) {
+                if let Some(object_id) = carried {
                     let prep = cmd.prep.unwrap_or_default();
                     match prep.as_str() {
                         // and the preposition is valid
                         "in" | "into" | "inside" => {
                             let obj_prep_name = command.obj_prep.unwrap_or_default();
                             // and the player or room has the object_prep
-                            if player.has(obj_prep_name.as_str())
-                                || curr_room.has(obj_prep_name.as_str())
-                            {
-                                let object = player.remove(&object_name).unwrap();
-                                let object_prep = if player.has(obj_prep_name.as_str()) {
-                                    player.find_object_mut(&obj_prep_name).unwrap()
-                                } else {
-                                    curr_room.find_object_mut(&obj_prep_name).unwrap()
-                                };
-                                if object_prep.accepts.contains(&object_name) {
-                                    // put the object in the object_prep
+                            let object_prep_id = command
+                                .target_prep
+                                .and_then(Entity::object)
+                                .filter(|id| world.object(*id, player, rooms).is_some());
+                            if let Some(object_prep_id) = object_prep_id {
+                                let accepts =
+                                    world.object(object_prep_id, player, rooms).is_some_and(
+                                        |object_prep| object_prep.accepts.contains(&object_name),
+                                    );
+                                if accepts {
+                                    // put the object in the object_prep, where it's only a name
+                                    let object =
+                                        world.remove_object(object_id, player, rooms).unwrap();
+                                    let object_prep =
+                                        world.object_mut(object_prep_id, player, rooms).unwrap();
                                     object_prep.inventory.push(object.name.to_string());
                                     // hardcoded :(
                                     if obj_prep_name == "mug"
                                         && object_prep.contains("hot water".to_string())
                                         && object_prep.contains("tea bag".to_string())
                                     {
-                                        *tea_time = Some(Instant::now())
+                                        state.vars.set(
+                                            "tea time",
+                                            Value::Time(state.clock.elapsed_secs()),
+                                        )
                                     }
                                     CommandResult {
                                         message: format!(
                                             "You put {} into {}.",
                                             object_name, obj_prep_name
                                         ),
+                                        success: true,
                                     }
                                 } else {
                                     CommandResult::cant_do_that("do".to_owned())
@@ -201,11 +500,13 @@ impl CommandHandler {
                 } else {
                     CommandResult {
                         message: format!("You don't have {}.", object_name),
+                        success: false,
                     }
                 }
             }
             None => CommandResult {
                 message: "You can't put nothing!".to_string(),
+                success: false,
             },
         }
     }
@@ -215,13 +516,18 @@ impl CommandHandler {
         command: Command,
         player: &mut Player,
         rooms: &mut [Room],
-        start_time: Instant,
+        world: &mut World,
+        state: &mut GameState,
     ) -> CommandResult {
-        let curr_room = player.get_curr_room_mut(rooms);
+        let here = Location::Room(player.at);
+        let carried = Location::Player(player.id);
         match command.obj {
             Some(object_name) => match object_name.as_str() {
                 "kettle" => {
-                    if let Some(kettle) = curr_room.find_object_mut("kettle") {
+                    let kettle = world
+                        .object_named(here, "kettle")
+                        .and_then(|id| world.object_mut(id, player, rooms));
+                    if let Some(kettle) = kettle {
                         if kettle.contains("water".to_owned()) {
                             let hot_water = Object {
                                 name: "hot water",
@@ -230,54 +536,82 @@ impl CommandHandler {
                                 ..Default::default()
                             };
                             kettle.inventory = vec!["hot water".into()];
-                            curr_room.inventory.add(hot_water);
+                            world.add_object(hot_water, here, player, rooms);
+                            state.play_sound("kettle.mp3");
                             CommandResult {
-                                message: "You turn on the kettle. There is now hot water inside the kettle.".to_owned()
+                                message: "You turn on the kettle. There is now hot water inside the kettle.".to_owned(),
+                                success: true,
                             }
                         } else {
                             CommandResult {
                                 message: "You might need to put water into the kettle first."
                                     .to_owned(),
+                                success: false,
                             }
                         }
                     } else {
                         CommandResult::no_object("kettle".to_owned())
                     }
                 }
-                "watch" if player.has("watch") => {
-                    let hour = 7 + (start_time.elapsed().as_secs() / 60);
-                    let minute = start_time.elapsed().as_secs() % 60;
-                    CommandResult {
-                        message: format!(
-                            "You glance at your watch. It reads {}:{:02}pm.",
-                            hour, minute
-                        ),
-                    }
-                }
+                "watch" if world.object_named(carried, "watch").is_some() => CommandResult {
+                    message: format!(
+                        "You glance at your watch. It reads {}.",
+                        watch_time(state.clock.elapsed())
+                    ),
+                    success: true,
+                },
                 _ => CommandResult::cant_do_that("use".to_owned()),
             },
             _ => CommandResult {
                 message: "Use what?".to_string(),
+                success: false,
+            },
+        }
+    }
+
+    /// Handles WAIT and WAIT [n] MINUTES
+    fn handle_wait(command: Command, state: &mut GameState) -> CommandResult {
+        let minutes = match command.obj {
+            Some(obj) => match obj.split_whitespace().next().unwrap_or_default().parse() {
+                Ok(minutes) => minutes,
+                Err(_) => return CommandResult::didnt_understand(obj),
             },
+            None => 1,
+        };
+        state.clock.advance(Duration::from_secs(minutes));
+        CommandResult {
+            message: if minutes == 1 {
+                "Time passes.".to_string()
+            } else {
+                format!("You wait for {} minutes.", minutes)
+            },
+            success: true,
         }
     }
 
-    fn handle_talk(command: Command, player: &mut Player, rooms: &mut [Room]) -> CommandResult {
+    fn handle_talk(
+        command: Command,
+        player: &mut Player,
+        rooms: &mut [Room],
+        world: &mut World,
+        state: &mut GameState,
+    ) -> CommandResult {
         match command.obj {
             Some(object_name) => {
-                if let Some(character) = player
-                    .get_curr_room_mut(rooms)
-                    .find_character_mut(&object_name)
-                {
-                    if !character.has_interacted {
-                        character.has_interacted = true;
-                        CommandResult {
-                            message: character.on_talk.to_owned(),
-                        }
-                    } else {
-                        CommandResult {
-                            message: character.on_talk_again.to_owned(),
+                let character = command
+                    .target
+                    .and_then(Entity::character)
+                    .and_then(|id| world.character(id, rooms));
+                if let Some(character) = character {
+                    match character.dialogue.greeting(player, rooms, state) {
+                        Some(node) => {
+                            let id = character.id;
+                            CommandHandler::run_dialogue_node(id, node, player, rooms, world, state)
                         }
+                        None => CommandResult {
+                            message: format!("The {} has nothing to say.", object_name),
+                            success: false,
+                        },
                     }
                 } else {
                     CommandResult::no_object(object_name)
@@ -285,44 +619,60 @@ impl CommandHandler {
             }
             None => CommandResult {
                 message: "Talk to what?".to_string(),
+                success: false,
             },
         }
     }
 
-    fn handle_ask(command: Command, player: &mut Player, rooms: &mut [Room]) -> CommandResult {
+    fn handle_ask(
+        command: Command,
+        player: &mut Player,
+        rooms: &mut [Room],
+        world: &mut World,
+        state: &mut GameState,
+    ) -> CommandResult {
         let cmd = command.clone();
         match command.obj {
             Some(character_name) => {
-                if let Some(character) = player
-                    .get_curr_room_mut(rooms)
-                    .find_character_mut(&character_name)
-                {
+                let character = command
+                    .target
+                    .and_then(Entity::character)
+                    .and_then(|id| world.character(id, rooms));
+                if let Some(character) = character {
                     let prep = cmd.prep.unwrap_or_default();
+                    let topic_name = command.obj_prep.unwrap_or_default();
                     match prep.as_str() {
-                        "for" => {
-                            let obj_prep_name = command.obj_prep.unwrap_or_default();
-                            // and the character has the object_prep
-                            if character.has(&obj_prep_name) {
-                                match character.give_object(obj_prep_name.as_str()) {
-                                    Some(object) if object.can_take => {
-                                        player.take_object(object);
-                                        CommandResult {
-                                            message: format!(
-                                                "{}. The {} gives you {}.",
-                                                character.on_ask, character_name, obj_prep_name
-                                            ),
-                                        }
-                                    }
-                                    Some(_) => CommandResult {
-                                        message: format!(
-                                            "The {} can't give you that.",
-                                            character_name
-                                        ),
-                                    },
-                                    None => CommandResult::doesnt_have_that(character_name),
-                                }
+                        "for" | "about" => {
+                            if let Some(node) =
+                                character.dialogue.topic(&topic_name, player, rooms, state)
+                            {
+                                let id = character.id;
+                                CommandHandler::run_dialogue_node(
+                                    id, node, player, rooms, world, state,
+                                )
+                            } else if prep == "for" {
+                                let id = character.id;
+                                CommandHandler::handle_ask_for(
+                                    id,
+                                    &topic_name,
+                                    player,
+                                    rooms,
+                                    world,
+                                )
                             } else {
-                                CommandResult::doesnt_have_that(character_name)
+                                CommandResult {
+                                    message: character
+                                        .dialogue
+                                        .msg_on_unknown_topic
+                                        .map(str::to_owned)
+                                        .unwrap_or_else(|| {
+                                            format!(
+                                                "The {} doesn't seem to know anything about that.",
+                                                character_name
+                                            )
+                                        }),
+                                    success: false,
+                                }
                             }
                         }
                         _ => CommandResult::didnt_understand(prep.to_owned()),
@@ -333,7 +683,280 @@ impl CommandHandler {
             }
             None => CommandResult {
                 message: "Ask who?".to_string(),
+                success: false,
+            },
+        }
+    }
+
+    /// Fallback for ASK ... FOR when the character has no topic for the object
+    fn handle_ask_for(
+        character: CharacterID,
+        object_name: &str,
+        player: &mut Player,
+        rooms: &mut [Room],
+        world: &mut World,
+    ) -> CommandResult {
+        let character_name = world.character(character, rooms).unwrap().name;
+        let held = Location::Character(character);
+        match world.object_named(held, object_name) {
+            Some(id) if world.object(id, player, rooms).unwrap().can_take => {
+                world.move_object(id, Location::Player(player.id), player, rooms);
+                CommandResult {
+                    message: format!("The {} gives you {}.", character_name, object_name),
+                    success: true,
+                }
+            }
+            Some(_) => CommandResult {
+                message: format!("The {} can't give you that.", character_name),
+                success: false,
             },
+            None => CommandResult::doesnt_have_that(character_name.to_owned()),
+        }
+    }
+
+    fn handle_give(
+        command: Command,
+        player: &mut Player,
+        rooms: &mut [Room],
+        world: &mut World,
+    ) -> CommandResult {
+        let cmd = command.clone();
+        let carried = Location::Player(player.id);
+        let carried = CommandHandler::takeable(&command, carried, player, rooms, world);
+        match command.obj {
+            Some(object_name) => {
+                let object_id = match carried {
+                    Some(id) => id,
+                    None => {
+                        return CommandResult {
+                            message: format!("You don't have {}.", object_name),
+                            success: false,
+                        }
+                    }
+                };
+                let prep = cmd.prep.unwrap_or_default();
+                if prep != "to" {
+                    return CommandResult {
+                        message: format!("Give the {} to who?", object_name),
+                        success: false,
+                    };
+                }
+                let character_name = command.obj_prep.unwrap_or_default();
+                let character_id = command.target_prep.and_then(Entity::character);
+                match character_id.and_then(|id| world.character(id, rooms)) {
+                    Some(character) => match character.find_trade(&object_name).cloned() {
+                        Some(trade) => {
+                            let held = Location::Character(character.id);
+                            world.move_object(object_id, held, player, rooms);
+                            let mut lines = vec![format!(
+                                "You give the {} to the {}.",
+                                object_name, character_name
+                            )];
+                            if let Some(msg) = trade.msg_on_trade {
+                                lines.push(msg.to_owned());
+                            }
+                            if let Some(reward) = trade.gives {
+                                let id = world.object_named(held, reward).filter(|id| {
+                                    world
+                                        .object(*id, player, rooms)
+                                        .is_some_and(|object| object.can_take)
+                                });
+                                if let Some(id) = id {
+                                    let carried = Location::Player(player.id);
+                                    world.move_object(id, carried, player, rooms);
+                                    lines.push(format!(
+                                        "The {} gives you {}.",
+                                        character_name, reward
+                                    ));
+                                }
+                            }
+                            CommandResult {
+                                message: lines.join(" "),
+                                success: true,
+                            }
+                        }
+                        None => CommandResult {
+                            message: character.msg_on_refuse.map(str::to_owned).unwrap_or_else(
+                                || format!("The {} doesn't want that.", character_name),
+                            ),
+                            success: false,
+                        },
+                    },
+                    None => CommandResult::no_object(character_name),
+                }
+            }
+            None => CommandResult {
+                message: "Give what?".to_string(),
+                success: false,
+            },
+        }
+    }
+
+    /// Handles TELL [character] TO [follow/stay]
+    fn handle_tell(command: Command, rooms: &mut [Room], world: &mut World) -> CommandResult {
+        let cmd = command.clone();
+        match command.obj {
+            Some(character_name) => {
+                let order = match cmd.prep.as_deref() {
+                    Some("to") => command.obj_prep.unwrap_or_default(),
+                    _ => {
+                        return CommandResult {
+                            message: format!("Tell the {} to do what?", character_name),
+                            success: false,
+                        }
+                    }
+                };
+                let character_id = command.target.and_then(Entity::character);
+                match character_id.and_then(|id| world.character_mut(id, rooms)) {
+                    Some(character) => match order.as_str() {
+                        "follow" | "come" | "follow me" | "come with me" => {
+                            if character.is_following {
+                                CommandResult {
+                                    message: format!(
+                                        "The {} is already following you.",
+                                        character_name
+                                    ),
+                                    success: false,
+                                }
+                            } else if character.will_follow {
+                                character.is_following = true;
+                                CommandResult {
+                                    message: format!(
+                                        "The {} gets up to follow you.",
+                                        character_name
+                                    ),
+                                    success: true,
+                                }
+                            } else {
+                                CommandResult {
+                                    message: format!(
+                                        "The {} doesn't seem interested in following you.",
+                                        character_name
+                                    ),
+                                    success: false,
+                                }
+                            }
+                        }
+                        "stay" | "wait" | "stay here" | "wait here" => {
+                            if character.is_following {
+                                character.is_following = false;
+                                CommandResult {
+                                    message: format!(
+                                        "The {} settles down to wait.",
+                                        character_name
+                                    ),
+                                    success: true,
+                                }
+                            } else {
+                                CommandResult {
+                                    message: format!(
+                                        "The {} isn't going anywhere.",
+                                        character_name
+                                    ),
+                                    success: false,
+                                }
+                            }
+                        }
+                        _ => CommandResult {
+                            message: format!(
+                                "The {} doesn't understand what you want.",
+                                character_name
+                            ),
+                            success: false,
+                        },
+                    },
+                    None => CommandResult::no_object(character_name),
+                }
+            }
+            None => CommandResult {
+                message: "Tell who?".to_string(),
+                success: false,
+            },
+        }
+    }
+
+    /// Picks one of the numbered choices offered by the current conversation
+    fn handle_choice(
+        choice: usize,
+        player: &mut Player,
+        rooms: &mut [Room],
+        world: &mut World,
+        state: &mut GameState,
+    ) -> CommandResult {
+        let character = state
+            .talking_to
+            .and_then(|name| world.character_named(player.at, name))
+            .and_then(|id| world.character(id, rooms));
+        match character {
+            Some(character) => {
+                let dialogue = &character.dialogue;
+                let next = dialogue.current.and_then(|node| {
+                    dialogue
+                        .available_choices(node, player, rooms, state)
+                        .get(choice.wrapping_sub(1))
+                        .map(|choice| choice.next)
+                });
+                match next {
+                    Some(node) => {
+                        let id = character.id;
+                        CommandHandler::run_dialogue_node(id, node, player, rooms, world, state)
+                    }
+                    None => CommandResult {
+                        message: "That isn't one of the options.".to_string(),
+                        success: false,
+                    },
+                }
+            }
+            None => {
+                state.talking_to = None;
+                CommandResult {
+                    message: "You aren't talking to anyone.".to_string(),
+                    success: false,
+                }
+            }
+        }
+    }
+
+    /// Shows a dialogue node, applies its effects and lists any choices that follow
+    fn run_dialogue_node(
+        character: CharacterID,
+        node: NodeID,
+        player: &mut Player,
+        rooms: &mut [Room],
+        world: &mut World,
+        state: &mut GameState,
+    ) -> CommandResult {
+        let offers_choices = !world
+            .character(character, rooms)
+            .unwrap()
+            .dialogue
+            .available_choices(node, player, rooms, state)
+            .is_empty();
+        let speaker = world.character_mut(character, rooms).unwrap();
+        let character_name = speaker.name;
+        speaker.dialogue.visit(node, offers_choices);
+        let dialogue_node = speaker.dialogue.node(node).clone();
+
+        let mut lines = vec![dialogue_node.text.to_owned()];
+        for effect in &dialogue_node.effects {
+            if let Some(msg) = effect.apply(character_name, player, rooms, world, state) {
+                lines.push(msg);
+            }
+        }
+
+        let dialogue = &world.character(character, rooms).unwrap().dialogue;
+        let choices = dialogue.available_choices(node, player, rooms, state);
+        if choices.is_empty() {
+            state.talking_to = None;
+        } else {
+            state.talking_to = Some(character_name);
+            for (i, choice) in choices.iter().enumerate() {
+                lines.push(format!("\t{}. {}", i + 1, choice.text));
+            }
+        }
+        CommandResult {
+            message: lines.join("\n"),
+            success: true,
         }
     }
 }
@@ -0,0 +1,55 @@
+use crate::game_state::GameState;
+use crate::player::Player;
+use crate::room::Room;
+use crate::vars::Value;
+
+/// A check against the current game state, used to gate dialogue nodes and choices
+/// and to pick between description variants
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    Flag(&'static str),
+    NotFlag(&'static str),
+    PlayerHas(&'static str),
+    PlayerLacks(&'static str),
+    /// this is the player's first time in the room they're in
+    FirstVisit,
+    /// game time has reached this many seconds
+    After(u64),
+    Before(u64),
+    /// an object the player holds or can see has something inside it
+    Contains(&'static str, &'static str),
+    /// the named character is in the room with the player
+    Present(&'static str),
+    /// a variable holds exactly this value
+    Is(&'static str, Value),
+    /// an integer variable is at least this much
+    AtLeast(&'static str, i64),
+    Not(Box<Condition>),
+    All(Vec<Condition>),
+    Any(Vec<Condition>),
+}
+
+impl Condition {
+    pub fn holds(&self, player: &Player, rooms: &[Room], state: &GameState) -> bool {
+        match self {
+            Condition::Flag(flag) => state.has_flag(flag),
+            Condition::NotFlag(flag) => !state.has_flag(flag),
+            Condition::PlayerHas(name) => player.has(name),
+            Condition::PlayerLacks(name) => !player.has(name),
+            Condition::FirstVisit => player.visits(player.at) <= 1,
+            Condition::After(time) => state.clock.elapsed_secs() >= *time,
+            Condition::Before(time) => state.clock.elapsed_secs() < *time,
+            Condition::Contains(container, content) => player
+                .find_object(container)
+                .or_else(|| player.get_curr_room(rooms).find_object(container))
+                .iter()
+                .any(|object| object.inventory.iter().any(|name| name == content)),
+            Condition::Present(name) => player.get_curr_room(rooms).find_character(name).is_some(),
+            Condition::Is(name, value) => state.vars.get(name) == Some(value),
+            Condition::AtLeast(name, min) => state.vars.int(name) >= *min,
+            Condition::Not(condition) => !condition.holds(player, rooms, state),
+            Condition::All(conditions) => conditions.iter().all(|c| c.holds(player, rooms, state)),
+            Condition::Any(conditions) => conditions.iter().any(|c| c.holds(player, rooms, state)),
+        }
+    }
+}
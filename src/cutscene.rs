@@ -0,0 +1,138 @@
+//! Cutscenes: text that plays out at its own pace instead of answering a command.
+//!
+//! Scenes are written in a small markup where every line is shown as it is, apart from:
+//!
+//! ```text
+//! [pause 5]          waits five seconds (fractions are fine)
+//! [wait]             waits for the player to press a key
+//! [sound gong.mp3]   plays a sound
+//! ```
+//!
+//! A scene only says what happens. How it is shown, and whether the waiting
+//! happens at all, is up to the `Renderer` playing it.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Beat {
+    /// one line of text; empty lines are kept as blank lines
+    Text(&'static str),
+    Pause(Duration),
+    WaitForKey,
+    Sound(&'static str),
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Cutscene {
+    pub beats: Vec<Beat>,
+}
+
+/// Somewhere a cutscene can be played
+pub trait Renderer {
+    fn text(&mut self, line: &str);
+    fn sound(&mut self, sound: &'static str);
+    /// Waits for `duration`. Returns true if the player asked to skip the rest of the scene.
+    fn pause(&mut self, duration: Duration) -> bool;
+    /// Waits for a keypress. Returns true if the player asked to skip the rest of the scene.
+    fn wait_for_key(&mut self) -> bool;
+}
+
+/// Collects the text and ignores the pacing, for frontends that can't wait
+impl Renderer for Vec<String> {
+    fn text(&mut self, line: &str) {
+        self.push(line.to_owned());
+    }
+
+    fn sound(&mut self, _sound: &'static str) {}
+
+    fn pause(&mut self, _duration: Duration) -> bool {
+        false
+    }
+
+    fn wait_for_key(&mut self) -> bool {
+        false
+    }
+}
+
+impl Cutscene {
+    /// Reads a scene written in markup. Errors give the line that couldn't be read.
+    pub fn parse(markup: &'static str) -> Result<Cutscene, String> {
+        let mut beats = vec![];
+        for (number, line) in markup.lines().enumerate() {
+            let directive = line
+                .trim()
+                .strip_prefix('[')
+                .and_then(|rest| rest.strip_suffix(']'))
+                .map(|inside| inside.splitn(2, ' ').collect::<Vec<_>>());
+            let beat = match directive.as_deref() {
+                Some(["wait"]) => Beat::WaitForKey,
+                // negative, endless and far too long pauses are all errors
+                Some(["pause", seconds]) => match seconds
+                    .trim()
+                    .parse::<f32>()
+                    .ok()
+                    .and_then(|seconds| Duration::try_from_secs_f32(seconds).ok())
+                {
+                    Some(duration) => Beat::Pause(duration),
+                    None => {
+                        return Err(format!(
+                            "line {}: `{}` isn't a number of seconds",
+                            number + 1,
+                            seconds
+                        ))
+                    }
+                },
+                Some(["sound", file]) => Beat::Sound(file.trim()),
+                Some(["pause"]) | Some(["sound"]) => {
+                    return Err(format!(
+                        "line {}: `{}` needs an argument",
+                        number + 1,
+                        line.trim()
+                    ))
+                }
+                // anything else in brackets is just text
+                _ => Beat::Text(line),
+            };
+            beats.push(beat);
+        }
+        Ok(Cutscene { beats })
+    }
+
+    /// Every line of text in the scene, without the pacing
+    pub fn text(&self) -> String {
+        let mut lines = vec![];
+        self.play(&mut lines, 1.0);
+        lines.join("\n")
+    }
+
+    /// Plays the scene, with pauses lasting `1 / speed` as long as written.
+    /// A pause too long to count waits for a key instead.
+    /// Once the player skips, the remaining text is shown all at once.
+    pub fn play(&self, renderer: &mut dyn Renderer, speed: f32) {
+        let mut skipping = false;
+        for beat in &self.beats {
+            match beat {
+                Beat::Text(line) => renderer.text(line),
+                _ if skipping => {}
+                Beat::Pause(duration) => {
+                    skipping = match Duration::try_from_secs_f32(duration.as_secs_f32() / speed) {
+                        Ok(duration) => renderer.pause(duration),
+                        Err(_) => renderer.wait_for_key(),
+                    }
+                }
+                Beat::WaitForKey => skipping = renderer.wait_for_key(),
+                Beat::Sound(sound) => renderer.sound(sound),
+            }
+        }
+    }
+}
+
+/// The scenes a world plays at the start, at either ending, and whenever it cues one by name
+#[derive(Debug, Clone, Default)]
+pub struct Cutscenes {
+    pub intro: Option<Cutscene>,
+    pub won: Option<Cutscene>,
+    pub lost: Option<Cutscene>,
+    pub named: HashMap<&'static str, Cutscene>,
+}
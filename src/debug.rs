@@ -0,0 +1,292 @@
+//! Commands for writers testing a world, so they don't have to play through it to
+//! check one scene. They start with `@` and only work when the game is started with
+//! `--debug`. Clock times are in game seconds, which are minutes on the watch.
+
+use crate::entity::Object;
+use crate::game::Game;
+use crate::inventory::Inventory;
+use crate::script::Hook;
+use crate::util::{intern, watch_time};
+use crate::vars::Value;
+use crate::world::Location;
+use std::iter;
+use std::time::Duration;
+
+pub const HELP: &str = "Debug commands:
+  @goto <room>             move to a room
+  @spawn <object>          make an object in the room
+  @remove <object>         take an object out of the game
+  @clock <n>               set the clock to n game seconds
+  @advance <n>             move the clock on n game seconds
+  @flag <name>             set a flag
+  @unflag <name>           clear a flag
+  @set <name> <value>      set a variable to true, false, a number or some text
+  @vars                    list the variables
+  @state                   show the game state
+  @inventory [<name>]      list what the player, a room, a character or an object holds
+  @events                  list what is scheduled to happen
+  @rules                   list the rules that fired last turn";
+
+pub const DISABLED: &str = "Debug commands are off. Start the game with --debug to use them.";
+
+/// Runs one debug command, given without its `@`
+pub fn run(game: &mut Game, line: &str) -> Vec<String> {
+    let line = line.trim();
+    let (command, arg) = match line.split_once(char::is_whitespace) {
+        Some((command, arg)) => (command, arg.trim()),
+        None => (line, ""),
+    };
+    let messages = match (command.to_lowercase().as_str(), arg) {
+        ("help", _) => vec![HELP.to_owned()],
+        ("goto", name) => goto(game, name),
+        ("spawn", name) if !name.is_empty() => {
+            let name = intern(&name.to_lowercase());
+            let object = Object {
+                name,
+                desc: "An object made for testing.",
+                can_take: true,
+                ..Default::default()
+            };
+            let here = Location::Room(game.player.at);
+            game.world
+                .add_object(object, here, &mut game.player, &mut game.rooms);
+            vec![format!("[Made a {} here.]", name)]
+        }
+        ("remove", name) if !name.is_empty() => {
+            if remove(game, name) {
+                vec![format!("[Removed the {}.]", name)]
+            } else {
+                vec![format!("[There is no {} here or on you.]", name)]
+            }
+        }
+        ("clock", secs) | ("advance", secs) => match secs.parse::<u64>() {
+            // timers and brewing tea only know how to go forwards
+            Ok(secs)
+                if command.eq_ignore_ascii_case("clock")
+                    && secs < game.state.clock.elapsed_secs() =>
+            {
+                let now = watch_time(game.state.clock.elapsed());
+                vec![format!("[The clock can't go back. It reads {}.]", now)]
+            }
+            Ok(secs) => {
+                let by = Duration::from_secs(secs);
+                match command.to_lowercase().as_str() {
+                    "clock" => game.state.clock.set_elapsed(by),
+                    _ => game.state.clock.advance(by),
+                }
+                let now = watch_time(game.state.clock.elapsed());
+                let mut messages = vec![format!("[The clock now reads {}.]", now)];
+                messages.extend(game.update_world());
+                messages
+            }
+            Err(_) => vec![format!("[`{}` isn't a number of game seconds.]", secs)],
+        },
+        ("flag", name) if !name.is_empty() => {
+            game.state.set_flag(name);
+            vec![format!("[Set {}.]", name)]
+        }
+        ("unflag", name) if !name.is_empty() => {
+            game.state.clear_flag(name);
+            vec![format!("[Cleared {}.]", name)]
+        }
+        ("set", arg) => match arg.split_once(char::is_whitespace) {
+            Some((name, value)) => {
+                // text can have spaces in it, but names can't
+                let value = match value.trim() {
+                    "true" => Value::Bool(true),
+                    "false" => Value::Bool(false),
+                    text => match text.parse() {
+                        Ok(number) => Value::Int(number),
+                        Err(_) => Value::Str(text.to_owned()),
+                    },
+                };
+                let reply = format!("[{} = {}]", name, value);
+                game.state.vars.set(name, value);
+                vec![reply]
+            }
+            None => vec!["[Use @set <name> <value>.]".to_owned()],
+        },
+        ("vars", _) => vec![game.state.vars.describe()],
+        ("state", _) => vec![state(game)],
+        ("inventory", name) => vec![inventory(game, name)],
+        ("events", _) => vec![events(game)],
+        ("rules", _) if game.state.rules_fired.is_empty() => {
+            vec!["No rules fired last turn.".to_owned()]
+        }
+        ("rules", _) => vec![game.state.rules_fired.join("\n")],
+        _ => vec![format!("[Unknown debug command `@{}`. Try @help.]", line)],
+    };
+    messages
+}
+
+fn goto(game: &mut Game, name: &str) -> Vec<String> {
+    match game.world.room_named(name) {
+        Some(room) => {
+            game.state.talking_to = None;
+            game.player.go(room);
+            vec![game.describe_room()]
+        }
+        None => vec![format!("[There is no room called {}.]", name)],
+    }
+}
+
+/// Takes an object away from the player, the room, or anyone in the room
+fn remove(game: &mut Game, name: &str) -> bool {
+    let here = game.player.get_curr_room(&game.rooms);
+    let held = here
+        .characters
+        .iter()
+        .map(|character| Location::Character(character.id));
+    let object = vec![
+        Location::Player(game.player.id),
+        Location::Room(game.player.at),
+    ]
+    .into_iter()
+    .chain(held)
+    .find_map(|at| game.world.object_named(at, name));
+    match object {
+        Some(id) => game
+            .world
+            .remove_object(id, &mut game.player, &mut game.rooms)
+            .is_some(),
+        None => false,
+    }
+}
+
+fn state(game: &Game) -> String {
+    let state = &game.state;
+    let here = game.player.get_curr_room(&game.rooms);
+    let lines = [
+        format!("room: {}", here.name),
+        format!(
+            "clock: {} game seconds ({})",
+            state.clock.elapsed_secs(),
+            watch_time(state.clock.elapsed())
+        ),
+        format!("won: {}, lost: {}", state.player_won(), state.player_lost()),
+        format!("talking to: {:?}", state.talking_to),
+        format!("seed: {}", state.seed),
+        format!("verbosity: {:?}", state.verbosity),
+        format!("time limit: {:?}", state.time_limit),
+        format!("goal: {:?}", state.goal),
+        format!(
+            "scenes waiting on a condition: {}",
+            state.scene_triggers.len()
+        ),
+        format!("timers fired: {:?}", state.timers_fired),
+        format!("sounds: {:?}", state.sounds),
+        format!("scenes: {:?}", state.scenes),
+        format!("rules fired: {:?}", state.rules_fired),
+        format!("vars:\n{}", state.vars.describe()),
+    ];
+    lines.join("\n")
+}
+
+/// What the player carries with no name, or else what a room, character or object holds
+fn inventory(game: &Game, name: &str) -> String {
+    if name.is_empty() {
+        return list(&game.player.inventory);
+    }
+    if let Some(room) = game.world.room_named(name) {
+        return list(&game.rooms[room.0].inventory);
+    }
+    let characters = game.rooms.iter().flat_map(|room| &room.characters);
+    if let Some(character) = characters.clone().find(|character| character.name == name) {
+        return list(&character.inventory);
+    }
+    let inventories = game
+        .rooms
+        .iter()
+        .map(|room| &room.inventory)
+        .chain(characters.map(|character| &character.inventory))
+        .chain(iter::once(&game.player.inventory));
+    for inventory in inventories {
+        if let Some(object) = inventory.find_object(name) {
+            if object.inventory.is_empty() {
+                return format!("The {} is empty.", name);
+            }
+            return object.inventory.join("\n");
+        }
+    }
+    format!("[There is nothing called {}.]", name)
+}
+
+/// One line per object, with its ID and what's inside it
+fn list(inventory: &Inventory) -> String {
+    if inventory.objects.is_empty() {
+        return "Nothing.".to_owned();
+    }
+    let lines: Vec<String> = inventory
+        .objects
+        .iter()
+        .map(|object| {
+            if object.inventory.is_empty() {
+                format!("{} (#{})", object.name, object.id.0)
+            } else {
+                format!(
+                    "{} (#{}), holding {}",
+                    object.name,
+                    object.id.0,
+                    object.inventory.join(", ")
+                )
+            }
+        })
+        .collect();
+    lines.join("\n")
+}
+
+/// Timed doors, characters' next stops and timer scripts still to come, soonest first
+fn events(game: &Game) -> String {
+    let now = game.state.clock.elapsed_secs();
+    let mut events: Vec<(u64, String)> = vec![];
+    for room in &game.rooms {
+        for door in &room.doors {
+            match door.opens_at {
+                Some(time) if !door.is_open && time > now => events.push((
+                    time,
+                    format!("the door {} of {} opens", door.direction, room.name),
+                )),
+                _ => {}
+            }
+        }
+        for character in room
+            .characters
+            .iter()
+            .filter(|character| !character.is_following)
+        {
+            let schedule = &character.schedule;
+            for stop in schedule.stops.iter().skip(schedule.next) {
+                let to = game.rooms[stop.room.0].name;
+                events.push((stop.time, format!("the {} goes to {}", character.name, to)));
+            }
+        }
+    }
+    for (i, script) in game.scripts.scripts.iter().enumerate() {
+        match script.hook {
+            Hook::Timer(time) if !game.state.timers_fired.contains(&i) => events.push((
+                time,
+                format!("the timer script at {} runs", script.location),
+            )),
+            _ => {}
+        }
+    }
+    if let Some(limit) = game.state.time_limit {
+        events.push((limit, "time runs out".to_owned()));
+    }
+    if events.is_empty() {
+        return "Nothing is scheduled.".to_owned();
+    }
+    events.sort_by_key(|(time, _)| *time);
+    let lines: Vec<String> = events
+        .into_iter()
+        .map(|(time, event)| {
+            format!(
+                "{} ({}): {}",
+                time,
+                watch_time(Duration::from_secs(time)),
+                event
+            )
+        })
+        .collect();
+    lines.join("\n")
+}
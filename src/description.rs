@@ -0,0 +1,84 @@
+//! Descriptions that change with the game. Rooms, objects and characters can carry
+//! variants that replace their plain description while a condition holds, and any
+//! description can fill in placeholders:
+//!
+//! - `{time}` - the time on the player's watch
+//! - `{contents}` - what the thing being described holds
+//! - `{contents of <object>}` - what an object the player holds or can see holds
+
+use crate::condition::Condition;
+use crate::game_state::GameState;
+use crate::player::Player;
+use crate::room::Room;
+use crate::util::{english_list, watch_time};
+
+/// Text that replaces a description while its condition holds. A room, object or
+/// character with several variants uses the first one that holds, and its plain `desc`
+/// when none do.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variant {
+    pub when: Condition,
+    pub text: &'static str,
+}
+
+/// Picks the first variant that holds, falling back on `desc`, and fills in its placeholders.
+/// `contents` is what the described thing holds.
+pub fn render(
+    desc: &'static str,
+    variants: &[Variant],
+    contents: &[String],
+    player: &Player,
+    rooms: &[Room],
+    state: &GameState,
+) -> String {
+    let text = variants
+        .iter()
+        .find(|variant| variant.when.holds(player, rooms, state))
+        .map_or(desc, |variant| variant.text);
+
+    let mut rendered = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => break,
+        };
+        rendered += &rest[..start];
+        let placeholder = &rest[start + 1..end];
+        match fill(placeholder, contents, player, rooms, state) {
+            Some(value) => rendered += &value,
+            // anything unknown is left for the reader to see
+            None => rendered += &rest[start..=end],
+        }
+        rest = &rest[end + 1..];
+    }
+    rendered + rest
+}
+
+fn fill(
+    placeholder: &str,
+    contents: &[String],
+    player: &Player,
+    rooms: &[Room],
+    state: &GameState,
+) -> Option<String> {
+    if placeholder == "time" {
+        return Some(watch_time(state.clock.elapsed()));
+    }
+    if placeholder == "contents" {
+        return Some(list_contents(contents));
+    }
+    let name = placeholder.strip_prefix("contents of ")?;
+    let object = player
+        .find_object(name)
+        .or_else(|| player.get_curr_room(rooms).find_object(name))?;
+    Some(list_contents(&object.inventory))
+}
+
+fn list_contents(contents: &[String]) -> String {
+    if contents.is_empty() {
+        "nothing".to_owned()
+    } else {
+        english_list(contents)
+    }
+}
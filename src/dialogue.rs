@@ -0,0 +1,205 @@
+use crate::condition::Condition;
+use crate::game_state::GameState;
+use crate::player::Player;
+use crate::room::{Room, RoomID};
+use crate::vars::Value;
+use crate::world::{Location, World};
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Hash)]
+pub struct NodeID(pub usize);
+
+/// A single line of dialogue, optionally followed by numbered choices
+#[derive(Debug, Clone, Default)]
+pub struct DialogueNode {
+    pub text: &'static str,
+    pub condition: Option<Condition>,
+    pub choices: Vec<Choice>,
+    pub effects: Vec<Effect>,
+    /// nodes marked `once` are skipped after they have been visited
+    pub once: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Choice {
+    pub text: &'static str,
+    pub next: NodeID,
+    pub condition: Option<Condition>,
+}
+
+/// Something the player can ASK a character ABOUT (or FOR)
+#[derive(Debug, Clone)]
+pub struct Topic {
+    pub name: &'static str,
+    /// candidate nodes, the first available one is used
+    pub nodes: Vec<NodeID>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Effect {
+    /// the character gives the named object to the player
+    GiveItem(&'static str),
+    /// the player hands the named object to the character
+    TakeItem(&'static str),
+    SetFlag(&'static str),
+    ClearFlag(&'static str),
+    OpenDoor(RoomID, &'static str),
+    /// sets a variable
+    Set(&'static str, Value),
+    /// adds to an integer variable
+    Add(&'static str, i64),
+    /// the character starts following the player around
+    Follow,
+    Stay,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Dialogue {
+    pub nodes: Vec<DialogueNode>,
+    /// candidate nodes for TALK, the first available one is used
+    pub greetings: Vec<NodeID>,
+    pub topics: Vec<Topic>,
+    pub msg_on_unknown_topic: Option<&'static str>,
+    pub visited: Vec<NodeID>,
+    /// the node whose choices are currently on offer, if any
+    pub current: Option<NodeID>,
+}
+
+impl Dialogue {
+    pub fn node(&self, id: NodeID) -> &DialogueNode {
+        &self.nodes[id.0]
+    }
+
+    pub fn has_visited(&self, id: NodeID) -> bool {
+        self.visited.contains(&id)
+    }
+
+    pub fn is_available(
+        &self,
+        id: NodeID,
+        player: &Player,
+        rooms: &[Room],
+        state: &GameState,
+    ) -> bool {
+        let node = self.node(id);
+        let condition_holds = match &node.condition {
+            Some(condition) => condition.holds(player, rooms, state),
+            None => true,
+        };
+        condition_holds && !(node.once && self.has_visited(id))
+    }
+
+    pub fn greeting(&self, player: &Player, rooms: &[Room], state: &GameState) -> Option<NodeID> {
+        self.first_available(&self.greetings, player, rooms, state)
+    }
+
+    pub fn topic(
+        &self,
+        name: &str,
+        player: &Player,
+        rooms: &[Room],
+        state: &GameState,
+    ) -> Option<NodeID> {
+        self.topics
+            .iter()
+            .find(|topic| topic.name == name)
+            .and_then(|topic| self.first_available(&topic.nodes, player, rooms, state))
+    }
+
+    pub fn available_choices(
+        &self,
+        id: NodeID,
+        player: &Player,
+        rooms: &[Room],
+        state: &GameState,
+    ) -> Vec<&Choice> {
+        self.node(id)
+            .choices
+            .iter()
+            .filter(|choice| match &choice.condition {
+                Some(condition) => condition.holds(player, rooms, state),
+                None => true,
+            })
+            .collect()
+    }
+
+    /// Marks the node as visited and, if it offers any choices right now, makes it the current node
+    pub fn visit(&mut self, id: NodeID, offers_choices: bool) {
+        if !self.has_visited(id) {
+            self.visited.push(id);
+        }
+        self.current = if offers_choices { Some(id) } else { None };
+    }
+
+    fn first_available(
+        &self,
+        ids: &[NodeID],
+        player: &Player,
+        rooms: &[Room],
+        state: &GameState,
+    ) -> Option<NodeID> {
+        ids.iter()
+            .copied()
+            .find(|id| self.is_available(*id, player, rooms, state))
+    }
+}
+
+impl Effect {
+    /// Applies the effect on behalf of the named character in the player's current room.
+    /// Returns a message describing what happened, if there is anything to say.
+    pub fn apply(
+        &self,
+        character_name: &str,
+        player: &mut Player,
+        rooms: &mut [Room],
+        world: &mut World,
+        state: &mut GameState,
+    ) -> Option<String> {
+        let character = world.character_named(player.at, character_name);
+        match self {
+            Effect::GiveItem(name) => {
+                let from = Location::Character(character?);
+                let object = world.object_named(from, name)?;
+                if !world.object(object, player, rooms)?.can_take {
+                    return None;
+                }
+                world.move_object(object, Location::Player(player.id), player, rooms);
+                Some(format!("The {} gives you {}.", character_name, name))
+            }
+            Effect::TakeItem(name) => {
+                let to = Location::Character(character?);
+                let object = world.object_named(Location::Player(player.id), name)?;
+                if !world.object(object, player, rooms)?.can_take {
+                    return None;
+                }
+                world.move_object(object, to, player, rooms);
+                Some(format!("You hand the {} {}.", character_name, name))
+            }
+            Effect::SetFlag(flag) => {
+                state.set_flag(flag);
+                None
+            }
+            Effect::ClearFlag(flag) => {
+                state.clear_flag(flag);
+                None
+            }
+            Effect::Set(name, value) => {
+                state.vars.set(name, value.clone());
+                None
+            }
+            Effect::Add(name, by) => {
+                state.vars.add(name, *by);
+                None
+            }
+            Effect::Follow | Effect::Stay => {
+                let character = world.character_mut(character?, rooms)?;
+                character.is_following = *self == Effect::Follow;
+                None
+            }
+            Effect::OpenDoor(room_id, direction) => {
+                let door = world.door_towards(*room_id, direction)?;
+                world.door_mut(door, rooms)?.is_open = true;
+                None
+            }
+        }
+    }
+}
@@ -1,22 +1,35 @@
+use crate::condition::Condition;
 use crate::room::RoomID;
+use crate::world::DoorID;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Door {
+    pub id: DoorID,
     pub target: RoomID,
     pub direction: &'static str,
     pub is_open: bool,
     pub msg_on_open: Option<&'static str>,
     pub msg_on_closed: Option<&'static str>,
+    /// sound file played when the player goes through
+    pub sound_on_open: Option<&'static str>,
+    /// game time at which the door unlocks by itself
+    pub opens_at: Option<u64>,
+    /// the door only lets the player through while this holds, even when open
+    pub guard: Option<Condition>,
 }
 
 impl Default for Door {
     fn default() -> Door {
         Door {
+            id: DoorID::default(),
             target: RoomID(0),
             direction: "",
             is_open: true,
             msg_on_open: None,
             msg_on_closed: None,
+            sound_on_open: None,
+            opens_at: None,
+            guard: None,
         }
     }
 }
@@ -1,12 +1,23 @@
+use crate::description::{render, Variant};
+use crate::game_state::GameState;
+use crate::player::Player;
+use crate::room::Room;
+use crate::world::ObjectID;
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Object {
+    pub id: ObjectID,
     pub name: &'static str,
     // pub desc: &'static str,
     pub desc: &'static str,
+    /// see `Variant`
+    pub variants: Vec<Variant>,
     pub inventory: Vec<String>,
     pub accepts: Vec<String>,
     pub can_take: bool,
     pub can_use: bool,
+    /// already part of the room's description, so not listed among what's lying around
+    pub scenery: bool,
     pub msg_on_take: Option<String>,
     pub msg_on_use: Option<String>,
 }
@@ -14,12 +25,15 @@ pub struct Object {
 impl Default for Object {
     fn default() -> Self {
         Object {
+            id: ObjectID::default(),
             name: "",
             desc: "",
+            variants: vec![],
             inventory: vec![],
             accepts: vec![],
             can_take: false,
             can_use: false,
+            scenery: false,
             msg_on_take: None,
             msg_on_use: None,
         }
@@ -27,6 +41,17 @@ impl Default for Object {
 }
 
 impl Object {
+    pub fn describe(&self, player: &Player, rooms: &[Room], state: &GameState) -> String {
+        render(
+            self.desc,
+            &self.variants,
+            &self.inventory,
+            player,
+            rooms,
+            state,
+        )
+    }
+
     pub fn contains(&self, name: String) -> bool {
         self.inventory.contains(&name)
     }
@@ -0,0 +1,126 @@
+use crate::actions::valid_actions;
+use crate::clock::Clock;
+use crate::game::{Ending, Game};
+use crate::history::History;
+use crate::room::RoomID;
+use std::time::Duration;
+
+/// How much each kind of progress is worth to an agent. Each world picks its own.
+#[derive(Debug, Clone, Default)]
+pub struct Rewards {
+    /// for entering a room for the first time in an episode
+    pub new_room: f64,
+    /// for each flag the first time it is set in an episode, e.g. finishing a recipe
+    pub flags: Vec<(&'static str, f64)>,
+    pub won: f64,
+    pub lost: f64,
+    /// added on every step, usually a small penalty
+    pub per_turn: f64,
+}
+
+/// What the agent gets back alongside each observation
+#[derive(Debug, Clone)]
+pub struct Info {
+    pub valid_actions: Vec<String>,
+    pub room: &'static str,
+    /// game time, in seconds
+    pub elapsed: u64,
+    pub ending: Option<Ending>,
+    pub seed: u64,
+}
+
+/// A world wrapped up as a reinforcement-learning environment.
+/// The clock only moves when the agent acts, so episodes run as fast as the agent can play.
+/// Each episode's seed drives the world's random numbers, so episodes of a world with
+/// randomness differ from seed to seed and repeat for the same seed.
+pub struct Environment {
+    pub new_game: fn(Clock) -> Game,
+    pub rewards: Rewards,
+    /// how much game time each step takes
+    pub turn_length: Duration,
+    game: Game,
+    visited: Vec<RoomID>,
+    flags_rewarded: Vec<&'static str>,
+}
+
+impl Environment {
+    pub fn new(new_game: fn(Clock) -> Game, rewards: Rewards) -> Self {
+        let turn_length = Duration::from_secs(1);
+        let mut environment = Environment {
+            new_game,
+            rewards,
+            turn_length,
+            game: new_game(Clock::turns(turn_length)),
+            visited: vec![],
+            flags_rewarded: vec![],
+        };
+        environment.start(0);
+        environment
+    }
+
+    /// Starts a new episode and returns the opening observation
+    pub fn reset(&mut self, seed: u64) -> (String, Info) {
+        self.game = (self.new_game)(Clock::turns(self.turn_length));
+        self.start(seed)
+    }
+
+    fn start(&mut self, seed: u64) -> (String, Info) {
+        // agents take back moves by resetting, not with undo
+        self.game.history = History::disabled();
+        self.game.state.reseed(seed);
+        self.visited = vec![self.game.player.at];
+        self.flags_rewarded = vec![];
+        (self.game.describe_room(), self.info())
+    }
+
+    /// Plays one action. Once the episode is done, further steps do nothing until `reset`.
+    pub fn step(&mut self, action: &str) -> (String, f64, bool, Info) {
+        if self.game.ending().is_some() {
+            return ("The game is over.".to_owned(), 0.0, true, self.info());
+        }
+        let mut messages = self.game.step(action);
+        messages.extend(self.game.cued_scenes().iter().map(|scene| scene.text()));
+        let observation = messages.join("\n");
+
+        let mut reward = self.rewards.per_turn;
+        if !self.visited.contains(&self.game.player.at) {
+            self.visited.push(self.game.player.at);
+            reward += self.rewards.new_room;
+        }
+        for (flag, value) in &self.rewards.flags {
+            if self.game.state.has_flag(flag) && !self.flags_rewarded.contains(flag) {
+                self.flags_rewarded.push(flag);
+                reward += value;
+            }
+        }
+        match self.game.ending() {
+            Some(Ending::Won) => reward += self.rewards.won,
+            Some(Ending::Lost) => reward += self.rewards.lost,
+            None => {}
+        }
+        (
+            observation,
+            reward,
+            self.game.ending().is_some(),
+            self.info(),
+        )
+    }
+
+    pub fn valid_actions(&self) -> Vec<String> {
+        valid_actions(&self.game)
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    fn info(&self) -> Info {
+        Info {
+            valid_actions: self.valid_actions(),
+            room: self.game.player.get_curr_room(&self.game.rooms).name,
+            elapsed: self.game.state.clock.elapsed_secs(),
+            ending: self.game.ending(),
+            seed: self.game.state.seed,
+        }
+    }
+}
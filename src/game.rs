@@ -0,0 +1,402 @@
+use crate::command::Command;
+use crate::command_handler::{full_verb, split_verb, Action, CommandHandler};
+use crate::cutscene::{Cutscene, Cutscenes};
+use crate::debug;
+use crate::game_state::GameState;
+use crate::graph;
+use crate::history::History;
+use crate::parser::Parser;
+use crate::player::Player;
+use crate::room::{Room, RoomID};
+use crate::rules::{Phase, Rulebook};
+use crate::script::{Hook, Outcome, Scripts};
+use crate::world::{Entity, World};
+use std::time::Duration;
+
+/// World-specific rules, run after every turn. Returns any messages for the player.
+/// Changes to objects and characters should go through the `World`.
+pub type UpdateFn = fn(&mut Player, &mut [Room], &mut World, &mut GameState) -> Vec<String>;
+
+// how long GO TO takes to get through each room on the way
+const TRANSIT_TIME: Duration = Duration::from_secs(1);
+
+// how many times `before` rules can swap one command for another in a turn, so two
+// rules that redirect to each other can't loop forever
+const MAX_REDIRECTS: usize = 4;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Ending {
+    Won,
+    Lost,
+}
+
+/// A world in play: everything needed to turn lines of input into lines of output
+#[derive(Clone)]
+pub struct Game {
+    pub player: Player,
+    pub rooms: Vec<Room>,
+    pub state: GameState,
+    pub history: History,
+    pub update: UpdateFn,
+    /// every line played so far, with the game time (in seconds) it was played at
+    pub log: Vec<(u64, String)>,
+    pub cutscenes: Cutscenes,
+    /// IDs and indexes over the rooms and everything in them
+    pub world: World,
+    pub scripts: Scripts,
+    pub rules: Rulebook,
+    /// whether the @ commands in `debug` are allowed
+    pub debug: bool,
+}
+
+impl Game {
+    /// Rebuilds the world's indexes from scratch. Turns keep them up to date as they
+    /// go, so this is only needed after changing the world without going through `World`.
+    pub fn reindex(&mut self) {
+        self.world.index(&mut self.player, &mut self.rooms);
+    }
+
+    pub fn describe_room(&self) -> String {
+        let here = self.player.get_curr_room(&self.rooms);
+        format!(
+            "{}\n{}",
+            here.get_display_name(),
+            here.describe(&self.player, &self.rooms, &self.state)
+        )
+    }
+
+    /// Whether the player may take back the move that brought about an ending
+    pub fn can_undo_ending(&self, ending: Ending) -> bool {
+        let allowed = match ending {
+            Ending::Won => self.history.after_win,
+            Ending::Lost => self.history.after_loss,
+        };
+        allowed && self.history.can_undo()
+    }
+
+    pub fn ending(&self) -> Option<Ending> {
+        if self.state.player_won() {
+            Some(Ending::Won)
+        } else if self.state.player_lost() {
+            Some(Ending::Lost)
+        } else {
+            None
+        }
+    }
+
+    /// Plays one line of input and returns everything the player should see
+    pub fn step(&mut self, input: &str) -> Vec<String> {
+        // debug commands look at the last turn without starting a new one
+        if let Some(line) = input.trim().strip_prefix('@') {
+            if !self.debug {
+                return vec![debug::DISABLED.to_owned()];
+            }
+            self.log
+                .push((self.state.clock.elapsed_secs(), input.trim().to_owned()));
+            return debug::run(self, line);
+        }
+        self.state.sounds.clear();
+        self.state.scenes.clear();
+        self.state.rules_fired.clear();
+        self.log
+            .push((self.state.clock.elapsed_secs(), input.trim().to_owned()));
+        // meta commands act on the history instead of the world, and journeys take
+        // several turns, so none of them go to the command handler
+        let words: Vec<&str> = input.split_whitespace().collect();
+        let line = words.join(" ").to_lowercase();
+        match split_verb(&line) {
+            Some((verb, "")) if verb.action == Action::Undo => {
+                if let Some(ending) = self.ending() {
+                    if !self.can_undo_ending(ending) {
+                        return vec!["The story is over; it can't be undone.".to_owned()];
+                    }
+                }
+                return if self.undo() {
+                    vec!["[Previous turn undone.]".to_owned(), self.describe_room()]
+                } else {
+                    vec!["You can't undo any further.".to_owned()]
+                };
+            }
+            Some((verb, "")) if verb.action == Action::Redo => {
+                return if self.redo() {
+                    vec!["[Turn redone.]".to_owned(), self.describe_room()]
+                } else {
+                    vec!["There is nothing to redo.".to_owned()]
+                };
+            }
+            Some((verb, "")) if verb.action == Action::Return => return self.go_back(),
+            Some((verb, name)) if verb.action == Action::Travel && !name.is_empty() => {
+                return self.travel_to(name)
+            }
+            _ => {}
+        }
+
+        let mut messages = vec![];
+        match Parser::parse(input) {
+            Ok(command) => {
+                self.history.record(&self.player, &self.rooms, &self.state);
+                let from = self.player.at;
+                messages.extend(self.act(command, 0));
+                messages.extend(self.run_room_scripts(from));
+                self.state.clock.tick();
+            }
+            Err(msg) => messages.push(msg.to_owned()),
+        }
+
+        // update game state and events
+        messages.extend(self.update_world());
+        self.player.look_around(&self.rooms);
+        messages.retain(|msg| !msg.is_empty());
+        messages
+    }
+
+    /// The parts of the game a command works on
+    fn turn(&mut self) -> Turn<'_> {
+        Turn {
+            player: &mut self.player,
+            rooms: &mut self.rooms,
+            world: &mut self.world,
+            state: &mut self.state,
+            scripts: &self.scripts,
+            rules: &self.rules,
+            update: self.update,
+        }
+    }
+
+    fn act(&mut self, command: Command, redirects: usize) -> Vec<String> {
+        self.turn().act(command, redirects)
+    }
+
+    /// The world's own rules, then any timer scripts that are due
+    pub fn update_world(&mut self) -> Vec<String> {
+        self.turn().update_world()
+    }
+
+    fn run_room_scripts(&mut self, from: RoomID) -> Vec<String> {
+        self.turn().run_room_scripts(from)
+    }
+
+    /// The scenes the world cued up during the last turn
+    pub fn cued_scenes(&self) -> Vec<&Cutscene> {
+        let named = &self.cutscenes.named;
+        self.state
+            .scenes
+            .iter()
+            .filter_map(|name| named.get(name))
+            .collect()
+    }
+
+    fn travel_to(&mut self, name: &str) -> Vec<String> {
+        let name = name.strip_prefix("the ").unwrap_or(name);
+        match self.world.room_named(name) {
+            Some(room) if self.player.has_visited(room) => self.travel(room),
+            _ => vec![format!("You don't know the way to {}.", name)],
+        }
+    }
+
+    fn go_back(&mut self) -> Vec<String> {
+        match self.player.came_from {
+            Some(room) => self.travel(room),
+            None => vec!["You haven't been anywhere to go back to.".to_owned()],
+        }
+    }
+
+    /// Walks the shortest way to a room through open doors, one room at a time.
+    /// Each room on the way takes time, and the trip stops early if anything
+    /// happens, or someone is in a room who wasn't when the player last saw it.
+    pub fn travel(&mut self, to: RoomID) -> Vec<String> {
+        let from = self.player.at;
+        if from == to {
+            return vec!["You're already there.".to_owned()];
+        }
+        let path = match graph::shortest_path(&self.rooms, from, to) {
+            Some(path) => path,
+            None => return vec!["You can't find a way there from here.".to_owned()],
+        };
+        self.state.talking_to = None;
+        // the whole trip is undone at once
+        self.history.record(&self.player, &self.rooms, &self.state);
+
+        let mut messages = vec![];
+        for (i, step) in path.iter().enumerate() {
+            let leg_from = self.player.at;
+            let command = Command {
+                verb: Some(step.direction.to_owned()),
+                obj: None,
+                prep: None,
+                obj_prep: None,
+                target: None,
+                target_prep: None,
+            };
+            // each leg is a move like any other, as far as rules and scripts can tell
+            messages.extend(self.act(command, 0));
+            self.state.clock.spend(TRANSIT_TIME);
+            let mut events = self.run_room_scripts(leg_from);
+            events.extend(self.update_world());
+            let met_someone = self.player.look_around(&self.rooms);
+            let interrupted = events.iter().any(|msg| !msg.is_empty())
+                || !self.state.scenes.is_empty()
+                || self.ending().is_some()
+                || self.player.at != step.to
+                || met_someone;
+            messages.extend(events);
+            if interrupted && i + 1 < path.len() {
+                messages.push("You stop before you get there.".to_owned());
+                break;
+            }
+        }
+        if self.player.at != from {
+            self.player.came_from = Some(from);
+        }
+        messages.retain(|msg| !msg.is_empty());
+        messages
+    }
+
+    pub fn undo(&mut self) -> bool {
+        let undone = self
+            .history
+            .undo(&mut self.player, &mut self.rooms, &mut self.state);
+        self.reindex();
+        undone
+    }
+
+    pub fn redo(&mut self) -> bool {
+        let redone = self
+            .history
+            .redo(&mut self.player, &mut self.rooms, &mut self.state);
+        self.reindex();
+        redone
+    }
+}
+
+/// One player's turn, borrowed from a `Game` or a `SharedWorld`, so that commands
+/// go through the same rules and scripts wherever they are played
+pub struct Turn<'a> {
+    pub player: &'a mut Player,
+    pub rooms: &'a mut [Room],
+    pub world: &'a mut World,
+    pub state: &'a mut GameState,
+    pub scripts: &'a Scripts,
+    pub rules: &'a Rulebook,
+    pub update: UpdateFn,
+}
+
+impl Turn<'_> {
+    /// Plays a command through the rulebook, the scripts around its verb and the verb's
+    /// handler. `redirects` counts the commands `before` rules have swapped in so far.
+    pub fn act(&mut self, mut command: Command, redirects: usize) -> Vec<String> {
+        Parser::resolve(&mut command, self.player, self.world);
+        let verb = full_verb(command.verb.as_deref().unwrap_or_default()).to_owned();
+        let target = self.target_name(&command);
+        // rules can be about things out of reach, so fall back on the name the player typed
+        let object = target.map(str::to_owned).or_else(|| command.obj.clone());
+        let object = object.as_deref();
+
+        let mut messages = vec![];
+        let mut redirect = None;
+        for rule in self.rules.phase(Phase::Before) {
+            if !rule.applies(&verb, object, self.player, self.rooms, self.state) {
+                continue;
+            }
+            messages.extend(rule.fire(self.player, self.rooms, self.world, self.state));
+            if rule.veto || rule.redirect.is_some() {
+                redirect = Some(rule.redirect);
+                break;
+            }
+        }
+        match redirect {
+            Some(Some(input)) if redirects < MAX_REDIRECTS => match Parser::parse(input) {
+                Ok(command) => {
+                    messages.extend(self.act(command, redirects + 1));
+                    return messages;
+                }
+                Err(msg) => {
+                    messages.push(msg.to_owned());
+                    return messages;
+                }
+            },
+            Some(_) => return messages,
+            None => {}
+        }
+
+        let before = self.run_scripts(
+            |hook| matches!(hook, Hook::Before(v, name) if *v == verb && Some(*name) == target),
+        );
+        messages.extend(before.messages);
+        if before.stopped {
+            return messages;
+        }
+        let instead = self
+            .rules
+            .phase(Phase::Instead)
+            .into_iter()
+            .find(|rule| rule.applies(&verb, object, self.player, self.rooms, self.state));
+        if let Some(rule) = instead {
+            messages.extend(rule.fire(self.player, self.rooms, self.world, self.state));
+            return messages;
+        }
+        let result = CommandHandler::handle_command(
+            command,
+            self.player,
+            self.rooms,
+            self.world,
+            self.state,
+        );
+        messages.push(result.message);
+        // `after` scripts and rules are for when the verb went through
+        if !result.success {
+            return messages;
+        }
+        let after = self.run_scripts(
+            |hook| matches!(hook, Hook::After(v, name) if *v == verb && Some(*name) == target),
+        );
+        messages.extend(after.messages);
+        for rule in self.rules.phase(Phase::After) {
+            if rule.applies(&verb, object, self.player, self.rooms, self.state) {
+                messages.extend(rule.fire(self.player, self.rooms, self.world, self.state));
+            }
+        }
+        messages
+    }
+
+    /// The world's own rules, then any timer scripts that are due
+    pub fn update_world(&mut self) -> Vec<String> {
+        let mut messages = (self.update)(self.player, self.rooms, self.world, self.state);
+        messages.extend(
+            self.scripts
+                .run_timers(self.player, self.rooms, self.world, self.state),
+        );
+        messages
+    }
+
+    fn run_scripts<F: Fn(&Hook) -> bool>(&mut self, matches: F) -> Outcome {
+        self.scripts
+            .run(matches, self.player, self.rooms, self.world, self.state)
+    }
+
+    /// Runs the scripts for leaving `from` and entering wherever the player is now, if they moved
+    pub fn run_room_scripts(&mut self, from: RoomID) -> Vec<String> {
+        let to = self.player.at;
+        if to == from {
+            return vec![];
+        }
+        let mut messages = self.run_scripts(|hook| *hook == Hook::Leave(from)).messages;
+        messages.extend(self.run_scripts(|hook| *hook == Hook::Enter(to)).messages);
+        messages
+    }
+
+    /// The name of the object or character a command is about
+    fn target_name(&self, command: &Command) -> Option<&'static str> {
+        match command.target? {
+            Entity::Object(id) => self
+                .world
+                .object(id, self.player, self.rooms)
+                .map(|object| object.name),
+            Entity::Character(id) => self
+                .world
+                .character(id, self.rooms)
+                .map(|character| character.name),
+            _ => None,
+        }
+    }
+}
@@ -1,8 +1,117 @@
-use std::time::Instant;
+use crate::clock::Clock;
+use crate::condition::Condition;
+use crate::vars::{Value, Vars};
+use std::time::Duration;
 
+/// How much is said about a room the player walks into
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verbosity {
+  /// the full description every time
+  Verbose,
+  /// the full description the first time only
+  Brief,
+  /// never the full description, unless the player looks
+  Superbrief,
+}
+
+/// The flags that end the story. Anything that can set a flag can set these.
+pub const WON_FLAG: &str = "won";
+pub const LOST_FLAG: &str = "lost";
+
+#[derive(Clone)]
 pub struct GameState {
-  pub player_won: bool,
-  pub player_lost: bool,
-  pub start_time: Instant,
-  pub tea_time: Option<Instant>,
+  pub clock: Clock,
+  /// the story's own variables, flags among them
+  pub vars: Vars,
+  /// the character the player is currently in conversation with
+  pub talking_to: Option<&'static str>,
+  /// for worlds with randomness, so a playthrough can be repeated. Set it with `reseed`.
+  pub seed: u64,
+  /// how far along the random numbers from `seed` have got
+  rng: u64,
+  pub verbosity: Verbosity,
+  /// sound files cued up during the current turn, for the frontend to play
+  pub sounds: Vec<&'static str>,
+  /// cutscenes cued up during the current turn, by name
+  pub scenes: Vec<&'static str>,
+  /// game time at which the player loses, for worlds that follow the generic rules
+  pub time_limit: Option<u64>,
+  /// the player wins once this holds, for worlds that follow the generic rules
+  pub goal: Option<Condition>,
+  /// scenes that play the first time their condition holds, for worlds that follow the generic rules
+  pub scene_triggers: Vec<(Condition, &'static str)>,
+  /// timer scripts that have already run, by their place in the world's scripts
+  pub timers_fired: Vec<usize>,
+  /// the rules that fired during the current turn, by name
+  pub rules_fired: Vec<&'static str>,
+}
+
+impl GameState {
+  pub fn new(clock: Clock) -> Self {
+    GameState {
+      clock,
+      vars: Vars::default(),
+      talking_to: None,
+      seed: 0,
+      rng: 0,
+      verbosity: Verbosity::Verbose,
+      sounds: vec![],
+      scenes: vec![],
+      time_limit: None,
+      goal: None,
+      scene_triggers: vec![],
+      timers_fired: vec![],
+      rules_fired: vec![],
+    }
+  }
+
+  pub fn has_flag(&self, flag: &str) -> bool {
+    self.vars.bool(flag)
+  }
+
+  pub fn set_flag(&mut self, flag: &str) {
+    self.vars.set(flag, Value::Bool(true));
+  }
+
+  pub fn clear_flag(&mut self, flag: &str) {
+    self.vars.remove(flag);
+  }
+
+  pub fn player_won(&self) -> bool {
+    self.has_flag(WON_FLAG)
+  }
+
+  pub fn player_lost(&self) -> bool {
+    self.has_flag(LOST_FLAG)
+  }
+
+  /// Starts the random numbers over from a new seed
+  pub fn reseed(&mut self, seed: u64) {
+    self.seed = seed;
+    self.rng = seed;
+  }
+
+  /// A random number below `below`, or 0 if that's 0. The same seed always
+  /// gives the same numbers, in the same order. (This is splitmix64.)
+  pub fn random(&mut self, below: u64) -> u64 {
+    self.rng = self.rng.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = self.rng;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^= z >> 31;
+    z.checked_rem(below).unwrap_or(0)
+  }
+
+  pub fn play_sound(&mut self, sound: &'static str) {
+    self.sounds.push(sound);
+  }
+
+  pub fn play_scene(&mut self, name: &'static str) {
+    self.scenes.push(name);
+  }
+
+  /// Moves every timer forward, as if the game had been paused for `by`
+  pub fn shift_clock(&mut self, by: Duration) {
+    self.clock.pause_for(by);
+  }
 }
@@ -0,0 +1,67 @@
+//! The rooms as a graph, joined by their open doors. Used by GO TO and RETURN,
+//! and there for characters and tools that need to find their way around.
+
+use crate::room::{Room, RoomID};
+use std::collections::VecDeque;
+
+/// One move along a path: the direction to go, and the room it leads to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step {
+    pub direction: &'static str,
+    pub to: RoomID,
+}
+
+/// The steps that can be taken out of a room right now
+pub fn exits(rooms: &[Room], from: RoomID) -> impl Iterator<Item = Step> + '_ {
+    rooms[from.0]
+        .doors
+        .iter()
+        .filter(|door| door.is_open)
+        .map(|door| Step {
+            direction: door.direction,
+            to: door.target,
+        })
+}
+
+/// Every room that can be reached from `from` through open doors, nearest first.
+/// `from` itself comes first.
+pub fn reachable(rooms: &[Room], from: RoomID) -> Vec<RoomID> {
+    let mut seen = vec![from];
+    let mut queue = VecDeque::from(vec![from]);
+    while let Some(room) = queue.pop_front() {
+        for step in exits(rooms, room) {
+            if !seen.contains(&step.to) {
+                seen.push(step.to);
+                queue.push_back(step.to);
+            }
+        }
+    }
+    seen
+}
+
+/// The fewest steps from one room to another through open doors, or `None` if
+/// there's no way through. Ties go to the doors listed first.
+pub fn shortest_path(rooms: &[Room], from: RoomID, to: RoomID) -> Option<Vec<Step>> {
+    // how each room was first reached
+    let mut came_by: Vec<Option<(RoomID, Step)>> = vec![None; rooms.len()];
+    let mut queue = VecDeque::from(vec![from]);
+    while let Some(room) = queue.pop_front() {
+        if room == to {
+            let mut path = vec![];
+            let mut at = to;
+            while let Some((previous, step)) = came_by[at.0] {
+                path.push(step);
+                at = previous;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for step in exits(rooms, room) {
+            if step.to != from && came_by[step.to.0].is_none() {
+                came_by[step.to.0] = Some((room, step));
+                queue.push_back(step.to);
+            }
+        }
+    }
+    None
+}
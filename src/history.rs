@@ -0,0 +1,109 @@
+use crate::game_state::GameState;
+use crate::player::Player;
+use crate::room::Room;
+use std::collections::VecDeque;
+use std::time::Instant;
+
+/// A copy of the whole world, taken at the start of a turn
+#[derive(Clone)]
+pub struct Snapshot {
+    pub player: Player,
+    pub rooms: Vec<Room>,
+    pub state: GameState,
+    pub taken_at: Instant,
+}
+
+impl Snapshot {
+    pub fn take(player: &Player, rooms: &[Room], state: &GameState) -> Self {
+        Snapshot {
+            player: player.clone(),
+            rooms: rooms.to_vec(),
+            state: state.clone(),
+            taken_at: Instant::now(),
+        }
+    }
+
+    /// Puts the world back the way it was. The game clock is rewound to the
+    /// moment the snapshot was taken, so timers line up with the restored world.
+    fn restore(self, player: &mut Player, rooms: &mut [Room], state: &mut GameState) {
+        *player = self.player;
+        for (room, saved) in rooms.iter_mut().zip(self.rooms) {
+            *room = saved;
+        }
+        *state = self.state;
+        state.shift_clock(self.taken_at.elapsed());
+    }
+}
+
+#[derive(Clone)]
+pub struct History {
+    undo_stack: VecDeque<Snapshot>,
+    redo_stack: Vec<Snapshot>,
+    /// how many turns can be undone
+    pub depth: usize,
+    pub enabled: bool,
+    /// whether the move that won the game can be taken back
+    pub after_win: bool,
+    pub after_loss: bool,
+}
+
+impl History {
+    pub fn new(depth: usize) -> Self {
+        History {
+            undo_stack: VecDeque::new(),
+            redo_stack: vec![],
+            depth,
+            enabled: depth > 0,
+            after_win: false,
+            after_loss: true,
+        }
+    }
+
+    pub fn disabled() -> Self {
+        History::new(0)
+    }
+
+    /// Records the world before a turn is played. Anything that was undone can no longer be redone.
+    pub fn record(&mut self, player: &Player, rooms: &[Room], state: &GameState) {
+        if !self.enabled {
+            return;
+        }
+        if self.undo_stack.len() == self.depth {
+            self.undo_stack.pop_front();
+        }
+        self.undo_stack
+            .push_back(Snapshot::take(player, rooms, state));
+        self.redo_stack.clear();
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.enabled && !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.enabled && !self.redo_stack.is_empty()
+    }
+
+    /// Restores the world to the start of the previous turn. Returns false if there is nothing to undo.
+    pub fn undo(&mut self, player: &mut Player, rooms: &mut [Room], state: &mut GameState) -> bool {
+        if !self.can_undo() {
+            return false;
+        }
+        let snapshot = self.undo_stack.pop_back().unwrap();
+        self.redo_stack.push(Snapshot::take(player, rooms, state));
+        snapshot.restore(player, rooms, state);
+        true
+    }
+
+    /// Replays a turn that was undone. Returns false if there is nothing to redo.
+    pub fn redo(&mut self, player: &mut Player, rooms: &mut [Room], state: &mut GameState) -> bool {
+        if !self.can_redo() {
+            return false;
+        }
+        let snapshot = self.redo_stack.pop().unwrap();
+        self.undo_stack
+            .push_back(Snapshot::take(player, rooms, state));
+        snapshot.restore(player, rooms, state);
+        true
+    }
+}
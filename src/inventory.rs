@@ -1,4 +1,5 @@
 use crate::entity::Object;
+use crate::world::ObjectID;
 
 #[derive(Debug, Clone)]
 pub struct Inventory {
@@ -10,8 +11,12 @@ impl Inventory {
         self.objects.iter().find(|object| object.name == name)
     }
 
-    pub fn find_object_mut(&mut self, name: &str) -> Option<&mut Object> {
-        self.objects.iter_mut().find(|object| object.name == name)
+    pub fn object(&self, id: ObjectID) -> Option<&Object> {
+        self.objects.iter().find(|object| object.id == id)
+    }
+
+    pub fn object_mut(&mut self, id: ObjectID) -> Option<&mut Object> {
+        self.objects.iter_mut().find(|object| object.id == id)
     }
 
     pub fn find_object_pos(&self, name: &str) -> Option<usize> {
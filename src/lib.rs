@@ -19,3 +19,61 @@ pub mod door;
 pub mod inventory;
 
 pub mod character;
+
+pub mod condition;
+
+pub mod dialogue;
+
+pub mod schedule;
+
+pub mod history;
+
+pub mod clock;
+
+pub mod game;
+
+pub mod transcript;
+
+pub mod worlds;
+
+pub mod actions;
+
+pub mod solver;
+
+pub mod protocol;
+
+pub mod environment;
+
+pub mod multiplayer;
+
+pub mod server;
+
+#[cfg(feature = "tui")]
+pub mod tui;
+
+#[cfg(feature = "line-editor")]
+pub mod line_editor;
+
+pub mod audio;
+
+pub mod save;
+
+pub mod cli;
+
+pub mod cutscene;
+
+pub mod description;
+
+pub mod map;
+
+pub mod graph;
+
+pub mod world;
+
+pub mod script;
+
+pub mod rules;
+
+pub mod vars;
+
+pub mod debug;
@@ -0,0 +1,234 @@
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::style::Print;
+use crossterm::terminal::{self, ClearType};
+use crossterm::tty::IsTty;
+use crossterm::{cursor, queue};
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+const MAX_HISTORY: usize = 500;
+
+/// Reads lines from the terminal with arrow-key editing, history that
+/// survives between games, and tab completion.
+pub struct LineEditor {
+    history: Vec<String>,
+    /// where history is saved; none means it is forgotten on exit
+    history_file: Option<PathBuf>,
+}
+
+impl LineEditor {
+    /// Starts an editor, picking up any history saved in `history_file`
+    pub fn new(history_file: Option<PathBuf>) -> Self {
+        let history = history_file
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|text| text.lines().map(str::to_owned).collect())
+            .unwrap_or_default();
+        LineEditor {
+            history,
+            history_file,
+        }
+    }
+
+    /// An editor for stdin, unless stdin isn't a terminal (e.g. input is piped in)
+    pub fn for_terminal(history_file: Option<PathBuf>) -> Option<Self> {
+        if io::stdin().is_tty() {
+            Some(LineEditor::new(history_file))
+        } else {
+            None
+        }
+    }
+
+    pub fn history(&self) -> &[String] {
+        &self.history
+    }
+
+    /// Remembers a line, and saves the history if there is somewhere to put it
+    pub fn add_history(&mut self, line: &str) {
+        if line.is_empty() || self.history.last().map(String::as_str) == Some(line) {
+            return;
+        }
+        self.history.push(line.to_owned());
+        if self.history.len() > MAX_HISTORY {
+            self.history.remove(0);
+        }
+        if let Some(path) = &self.history_file {
+            // losing history isn't worth interrupting the game for
+            let _ = fs::write(path, self.history.join("\n") + "\n");
+        }
+    }
+
+    /// Reads one line. `complete` gets the line so far and returns where the
+    /// text it completes starts, and the candidates. Returns `None` on Ctrl-C or Ctrl-D.
+    pub fn read_line<F>(&mut self, prompt: &str, complete: F) -> io::Result<Option<String>>
+    where
+        F: Fn(&str) -> (usize, Vec<String>),
+    {
+        terminal::enable_raw_mode()?;
+        let result = self.edit(prompt, complete);
+        terminal::disable_raw_mode()?;
+        let line = result?;
+        if let Some(line) = &line {
+            self.add_history(line);
+        }
+        Ok(line)
+    }
+
+    fn edit<F>(&mut self, prompt: &str, complete: F) -> io::Result<Option<String>>
+    where
+        F: Fn(&str) -> (usize, Vec<String>),
+    {
+        let mut out = io::stdout();
+        let mut line: Vec<char> = vec![];
+        let mut pos = 0;
+        let mut history_pos = self.history.len();
+        // a second tab in a row lists the candidates
+        let mut tabbed = false;
+        redraw(&mut out, prompt, &line, pos)?;
+        loop {
+            let key = match event::read()? {
+                Event::Key(key) if key.kind != KeyEventKind::Release => key,
+                _ => continue,
+            };
+            let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+            if key.code != KeyCode::Tab {
+                tabbed = false;
+            }
+            match key.code {
+                KeyCode::Enter => {
+                    queue!(out, Print("\r\n"))?;
+                    out.flush()?;
+                    return Ok(Some(line.iter().collect::<String>().trim().to_owned()));
+                }
+                KeyCode::Char('c') if ctrl => return Ok(None),
+                KeyCode::Char('d') if ctrl && line.is_empty() => return Ok(None),
+                KeyCode::Char('a') if ctrl => pos = 0,
+                KeyCode::Char('e') if ctrl => pos = line.len(),
+                KeyCode::Char(c) => {
+                    line.insert(pos, c);
+                    pos += 1;
+                }
+                KeyCode::Backspace if pos > 0 => {
+                    pos -= 1;
+                    line.remove(pos);
+                }
+                KeyCode::Delete if pos < line.len() => {
+                    line.remove(pos);
+                }
+                KeyCode::Left => pos = pos.saturating_sub(1),
+                KeyCode::Right => pos = (pos + 1).min(line.len()),
+                KeyCode::Home => pos = 0,
+                KeyCode::End => pos = line.len(),
+                KeyCode::Up if history_pos > 0 => {
+                    history_pos -= 1;
+                    line = self.history[history_pos].chars().collect();
+                    pos = line.len();
+                }
+                KeyCode::Down if history_pos < self.history.len() => {
+                    history_pos += 1;
+                    line = match self.history.get(history_pos) {
+                        Some(entry) => entry.chars().collect(),
+                        None => vec![],
+                    };
+                    pos = line.len();
+                }
+                KeyCode::Tab => {
+                    let typed: String = line[..pos].iter().collect();
+                    let (start, candidates) = complete(&typed);
+                    let start = typed[..start].chars().count();
+                    let common = common_prefix(&candidates);
+                    if common.chars().count() > pos - start {
+                        let rest: String = line[pos..].iter().collect();
+                        line = line[..start]
+                            .iter()
+                            .copied()
+                            .chain(common.chars())
+                            .collect();
+                        // a single candidate is finished off so the next word can start
+                        if candidates.len() == 1 && rest.is_empty() {
+                            line.push(' ');
+                        }
+                        pos = line.len();
+                        line.extend(rest.chars());
+                    } else if tabbed && candidates.len() > 1 {
+                        queue!(
+                            out,
+                            Print("\r\n"),
+                            Print(candidates.join("   ")),
+                            Print("\r\n")
+                        )?;
+                    }
+                    tabbed = true;
+                }
+                _ => {}
+            }
+            redraw(&mut out, prompt, &line, pos)?;
+        }
+    }
+}
+
+fn redraw<W: Write>(out: &mut W, prompt: &str, line: &[char], pos: usize) -> io::Result<()> {
+    let text: String = line.iter().collect();
+    queue!(
+        out,
+        cursor::MoveToColumn(0),
+        terminal::Clear(ClearType::UntilNewLine),
+        Print(prompt),
+        Print(text),
+        cursor::MoveToColumn((prompt.chars().count() + pos) as u16)
+    )?;
+    out.flush()
+}
+
+/// The longest start that every candidate shares
+pub fn common_prefix(candidates: &[String]) -> String {
+    let first = match candidates.first() {
+        Some(first) => first,
+        None => return String::new(),
+    };
+    let mut len = first.len();
+    for candidate in &candidates[1..] {
+        len = first
+            .char_indices()
+            .zip(candidate.chars())
+            .take_while(|((i, a), b)| *i < len && a == b)
+            .map(|((i, a), _)| i + a.len_utf8())
+            .last()
+            .unwrap_or(0);
+    }
+    first[..len].to_owned()
+}
+
+/// Waits up to `timeout` (or for ever) for the player to press Enter. Returns
+/// whether they did; Ctrl-C counts, so a pause can always be got out of.
+pub fn wait_for_enter(timeout: Option<Duration>) -> io::Result<bool> {
+    terminal::enable_raw_mode()?;
+    let result = wait_for_enter_raw(timeout);
+    terminal::disable_raw_mode()?;
+    result
+}
+
+fn wait_for_enter_raw(timeout: Option<Duration>) -> io::Result<bool> {
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    loop {
+        let left = match deadline {
+            Some(deadline) => deadline.saturating_duration_since(Instant::now()),
+            None => Duration::from_secs(60),
+        };
+        if !event::poll(left)? {
+            if deadline.is_some() {
+                return Ok(false);
+            }
+            continue;
+        }
+        if let Event::Key(key) = event::read()? {
+            let ctrl_c =
+                key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+            if key.kind != KeyEventKind::Release && (key.code == KeyCode::Enter || ctrl_c) {
+                return Ok(true);
+            }
+        }
+    }
+}
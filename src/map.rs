@@ -0,0 +1,143 @@
+//! The MAP command: the rooms the player has been to, drawn in ASCII and laid out
+//! by the compass directions of the doors between them.
+
+use crate::player::Player;
+use crate::room::{Room, RoomID};
+use std::collections::VecDeque;
+
+// columns between the widest room label and the next one over
+const GAP: usize = 3;
+
+fn offset(direction: &str) -> Option<(i32, i32)> {
+    match direction {
+        "north" => Some((0, -1)),
+        "south" => Some((0, 1)),
+        "east" => Some((1, 0)),
+        "west" => Some((-1, 0)),
+        _ => None,
+    }
+}
+
+/// Places the rooms the player has visited on a grid, starting from where they are.
+/// Rooms only reached through non-compass exits, or that would land on a room
+/// already placed, are left off.
+pub fn layout(player: &Player, rooms: &[Room]) -> Vec<(RoomID, (i32, i32))> {
+    let mut placed = vec![(player.at, (0, 0))];
+    let mut queue = VecDeque::from(vec![(player.at, (0, 0))]);
+    while let Some((id, (x, y))) = queue.pop_front() {
+        for door in &rooms[id.0].doors {
+            let (dx, dy) = match offset(door.direction) {
+                Some(offset) => offset,
+                None => continue,
+            };
+            let position = (x + dx, y + dy);
+            if !player.has_visited(door.target)
+                || placed
+                    .iter()
+                    .any(|&(room, at)| room == door.target || at == position)
+            {
+                continue;
+            }
+            placed.push((door.target, position));
+            queue.push_back((door.target, position));
+        }
+    }
+    placed
+}
+
+/// Draws the map, with the room the player is in marked `*` and ways they haven't
+/// been yet marked `?`.
+pub fn render(player: &Player, rooms: &[Room]) -> String {
+    let placed = layout(player, rooms);
+    let label = |id: RoomID| match id == player.at {
+        true => format!("[*{}*]", rooms[id.0].name),
+        false => format!("[{}]", rooms[id.0].name),
+    };
+    let width = placed
+        .iter()
+        .map(|&(id, _)| label(id).chars().count())
+        .max()
+        .unwrap_or(0);
+    let min_x = placed.iter().map(|(_, (x, _))| *x).min().unwrap_or(0);
+    let max_x = placed.iter().map(|(_, (x, _))| *x).max().unwrap_or(0);
+    let min_y = placed.iter().map(|(_, (_, y))| *y).min().unwrap_or(0);
+    let max_y = placed.iter().map(|(_, (_, y))| *y).max().unwrap_or(0);
+
+    // rooms sit on odd rows, with connectors on the rows between them
+    let columns = (max_x - min_x + 1) as usize * (width + GAP) + GAP;
+    let mut canvas = vec![vec![' '; columns]; (max_y - min_y + 1) as usize * 2 + 1];
+    let cell = |(x, y): (i32, i32)| {
+        (
+            (x - min_x) as usize * (width + GAP) + GAP,
+            (y - min_y) as usize * 2 + 1,
+        )
+    };
+    let mut spans = vec![];
+    for &(id, position) in &placed {
+        let (left, row) = cell(position);
+        let text = label(id);
+        let start = left + (width - text.chars().count()) / 2;
+        for (i, c) in text.chars().enumerate() {
+            canvas[row][start + i] = c;
+        }
+        spans.push((id, position, start, start + text.chars().count()));
+    }
+    for &(id, (x, y), start, end) in &spans {
+        let (left, row) = cell((x, y));
+        let middle = left + width / 2;
+        for door in &rooms[id.0].doors {
+            let (dx, dy) = match offset(door.direction) {
+                Some(offset) => offset,
+                None => continue,
+            };
+            let neighbour = spans
+                .iter()
+                .find(|&&(other, at, _, _)| other == door.target && at == (x + dx, y + dy));
+            match (neighbour, player.has_visited(door.target)) {
+                (Some(&(_, _, other_start, _)), _) if dx == 1 => {
+                    canvas[row][end..other_start]
+                        .iter_mut()
+                        .for_each(|c| *c = '-');
+                }
+                (Some(_), _) if dx == 0 => canvas[(row as i32 + dy) as usize][middle] = '|',
+                // west doors are drawn from the other side, and visited rooms
+                // that couldn't be placed next to this one are left unconnected
+                (Some(_), _) | (None, true) => {}
+                (None, false) => match (dx, dy) {
+                    (1, _) => {
+                        canvas[row][end] = '-';
+                        canvas[row][end + 1] = '?';
+                    }
+                    (-1, _) => {
+                        canvas[row][start - 1] = '-';
+                        canvas[row][start - 2] = '?';
+                    }
+                    _ => canvas[(row as i32 + dy) as usize][middle] = '?',
+                },
+            }
+        }
+    }
+
+    let lines: Vec<String> = canvas
+        .iter()
+        .map(|row| row.iter().collect::<String>().trim_end().to_owned())
+        .filter(|line| !line.is_empty())
+        .collect();
+    let indent = lines
+        .iter()
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    let mut map: Vec<String> = lines.iter().map(|line| line[indent..].to_owned()).collect();
+    map.push(String::new());
+    map.push("*: you are here   ?: unexplored".to_owned());
+    let missing: Vec<&str> = (0..rooms.len())
+        .map(RoomID)
+        .filter(|&id| player.has_visited(id) && !placed.iter().any(|&(room, _)| room == id))
+        .map(|id| rooms[id.0].name)
+        .collect();
+    if !missing.is_empty() {
+        map.push(format!("Not shown: {}.", missing.join(", ")));
+    }
+    map.join("\n")
+}
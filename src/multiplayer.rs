@@ -0,0 +1,312 @@
+use crate::entity::Object;
+use crate::game::{Ending, Game, Turn, UpdateFn};
+use crate::game_state::GameState;
+use crate::parser::Parser;
+use crate::player::Player;
+use crate::room::{Room, RoomID};
+use crate::rules::Rulebook;
+use crate::script::Scripts;
+use crate::world::{Location, ObjectID, World};
+use std::time::Instant;
+
+pub use crate::world::PlayerID;
+
+/// A line of output for one player
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    pub to: PlayerID,
+    pub text: String,
+}
+
+/// One player's place in a shared world
+struct Seat {
+    name: String,
+    player: Player,
+    /// the character this player is in conversation with, kept apart from everyone else's
+    talking_to: Option<&'static str>,
+}
+
+/// Several players in the same rooms, taking turns one command at a time.
+/// Turns are played in the order they arrive, so whoever asks first gets the mug.
+/// Commands go through the world's rules and scripts just as they would for one player.
+/// The world's update function and timers run once per turn, for the player whose
+/// turn it is, and their events reach only them. The clock stands still while nobody is playing.
+pub struct SharedWorld {
+    seats: Vec<Option<Seat>>,
+    /// every player who joins starts as a copy of this one
+    newcomer: Player,
+    pub rooms: Vec<Room>,
+    pub state: GameState,
+    pub update: UpdateFn,
+    /// indexes over the rooms and everyone's inventories
+    pub world: World,
+    pub scripts: Scripts,
+    pub rules: Rulebook,
+    /// when the last player left, or the world was made if nobody has joined yet
+    empty_since: Option<Instant>,
+}
+
+impl SharedWorld {
+    pub fn new(game: Game) -> Self {
+        let mut world = game.world;
+        // the newcomer is only a pattern; each seat's copy is indexed as it joins
+        world.remove_player(&game.player);
+        SharedWorld {
+            seats: vec![],
+            newcomer: game.player,
+            rooms: game.rooms,
+            state: game.state,
+            update: game.update,
+            world,
+            scripts: game.scripts,
+            rules: game.rules,
+            empty_since: Some(Instant::now()),
+        }
+    }
+
+    pub fn player(&self, id: PlayerID) -> Option<&Player> {
+        self.seats.get(id.0)?.as_ref().map(|seat| &seat.player)
+    }
+
+    pub fn name(&self, id: PlayerID) -> Option<&str> {
+        self.seats
+            .get(id.0)?
+            .as_ref()
+            .map(|seat| seat.name.as_str())
+    }
+
+    pub fn players(&self) -> impl Iterator<Item = (PlayerID, &Player)> {
+        self.seats
+            .iter()
+            .enumerate()
+            .filter_map(|(i, seat)| seat.as_ref().map(|seat| (PlayerID(i), &seat.player)))
+    }
+
+    pub fn ending(&self) -> Option<Ending> {
+        if self.state.player_won() {
+            Some(Ending::Won)
+        } else if self.state.player_lost() {
+            Some(Ending::Lost)
+        } else {
+            None
+        }
+    }
+
+    /// Adds a player to the starting room and tells everyone already there.
+    /// The seat of someone who has left is given to the next player to join.
+    pub fn join(&mut self, name: &str) -> (PlayerID, Vec<Message>) {
+        let id = match self.seats.iter().position(Option::is_none) {
+            Some(free) => PlayerID(free),
+            None => {
+                self.seats.push(None);
+                PlayerID(self.seats.len() - 1)
+            }
+        };
+        if let Some(since) = self.empty_since.take() {
+            self.state.shift_clock(since.elapsed());
+        }
+        let mut player = self.newcomer.clone();
+        player.id = id;
+        self.world.add_player(&mut player);
+        let at = player.at;
+        self.seats[id.0] = Some(Seat {
+            name: name.to_owned(),
+            player,
+            talking_to: None,
+        });
+        let mut messages = self.tell_room(at, id, format!("{} arrives.", name));
+        messages.push(Message {
+            to: id,
+            text: self.describe_room(id),
+        });
+        (id, messages)
+    }
+
+    /// Removes a player. Anything they were carrying is left behind for the others.
+    pub fn leave(&mut self, id: PlayerID) -> Vec<Message> {
+        let seat = match self.seats.get_mut(id.0).and_then(Option::take) {
+            Some(seat) => seat,
+            None => return vec![],
+        };
+        let mut player = seat.player;
+        let carried: Vec<ObjectID> = player.inventory.objects.iter().map(|o| o.id).collect();
+        for object in carried {
+            let here = Location::Room(player.at);
+            self.world
+                .move_object(object, here, &mut player, &mut self.rooms);
+        }
+        self.world.remove_player(&player);
+        if self.players().next().is_none() {
+            self.empty_since = Some(Instant::now());
+        }
+        self.tell_room(player.at, id, format!("{} has left.", seat.name))
+    }
+
+    /// The room's name and description, plus whoever else is in it
+    pub fn describe_room(&self, id: PlayerID) -> String {
+        let player = match self.player(id) {
+            Some(player) => player,
+            None => return String::new(),
+        };
+        let here = player.get_curr_room(&self.rooms);
+        let mut description = format!(
+            "{}\n{}",
+            here.get_display_name(),
+            here.describe(player, &self.rooms, &self.state)
+        );
+        for name in self
+            .players_in(player.at, id)
+            .filter_map(|(other, _)| self.name(other))
+        {
+            description += &format!("\n{} is here.", name);
+        }
+        description
+    }
+
+    /// Plays one line of input for one player. Returns the output for that player
+    /// and anything the other players notice.
+    pub fn step(&mut self, id: PlayerID, input: &str) -> Vec<Message> {
+        let mut seat = match self.seats.get_mut(id.0).and_then(Option::take) {
+            Some(seat) => seat,
+            None => return vec![],
+        };
+        let name = seat.name.clone();
+        let from = seat.player.at;
+        self.state.sounds.clear();
+        self.state.scenes.clear();
+        self.state.rules_fired.clear();
+        let mut messages = vec![];
+
+        // speech is passed on word for word, so it skips the parser
+        let input = input.trim();
+        if let Some(speech) = speech(input) {
+            self.seats[id.0] = Some(seat);
+            if speech.is_empty() {
+                messages.push(Message {
+                    to: id,
+                    text: "Say what?".to_owned(),
+                });
+                return messages;
+            }
+            messages.push(Message {
+                to: id,
+                text: format!("You say, \"{}\"", speech),
+            });
+            messages.extend(self.tell_room(from, id, format!("{} says, \"{}\"", name, speech)));
+            return messages;
+        }
+
+        let room_objects = object_names(&self.rooms[from.0].inventory.objects);
+        let carried = object_names(&seat.player.inventory.objects);
+        let mut looked = false;
+        match Parser::parse(input) {
+            Ok(command) => {
+                looked = command.verb.as_deref() == Some("look") && command.obj.is_none();
+                self.state.talking_to = seat.talking_to;
+                let mut turn = self.turn(&mut seat.player);
+                let mut output = turn.act(command, 0);
+                output.extend(turn.run_room_scripts(from));
+                seat.talking_to = self.state.talking_to.take();
+                for text in output {
+                    messages.push(Message { to: id, text });
+                }
+                self.state.clock.tick();
+            }
+            Err(msg) => messages.push(Message {
+                to: id,
+                text: msg.to_owned(),
+            }),
+        }
+        let to = seat.player.at;
+        let taken: Vec<&'static str> = object_names(&seat.player.inventory.objects)
+            .into_iter()
+            .filter(|name| room_objects.contains(name) && !carried.contains(name))
+            .collect();
+        self.seats[id.0] = Some(seat);
+
+        // let the others know what they saw
+        for object in taken {
+            messages.extend(self.tell_room(from, id, format!("{} takes the {}.", name, object)));
+        }
+        if from != to {
+            let leaving = match self.rooms[from.0]
+                .doors
+                .iter()
+                .find(|door| door.target == to)
+            {
+                Some(door) => format!("{} leaves to the {}.", name, door.direction),
+                None => format!("{} leaves.", name),
+            };
+            messages.extend(self.tell_room(from, id, leaving));
+            messages.extend(self.tell_room(to, id, format!("{} arrives.", name)));
+        }
+        if from != to || looked {
+            let others: Vec<String> = self
+                .players_in(to, id)
+                .filter_map(|(other, _)| self.name(other).map(str::to_owned))
+                .collect();
+            for other in others {
+                messages.push(Message {
+                    to: id,
+                    text: format!("{} is here.", other),
+                });
+            }
+        }
+
+        // the world moves on once per turn, however many players there are
+        let mut seat = self.seats[id.0].take().unwrap();
+        let update = self.turn(&mut seat.player).update_world();
+        self.seats[id.0] = Some(seat);
+        for text in update {
+            messages.push(Message { to: id, text });
+        }
+        messages.retain(|msg| !msg.text.is_empty());
+        messages
+    }
+
+    /// A turn for one player, who is out of their seat while it's played
+    fn turn<'a>(&'a mut self, player: &'a mut Player) -> Turn<'a> {
+        Turn {
+            player,
+            rooms: &mut self.rooms,
+            world: &mut self.world,
+            state: &mut self.state,
+            scripts: &self.scripts,
+            rules: &self.rules,
+            update: self.update,
+        }
+    }
+
+    fn players_in(
+        &self,
+        room: RoomID,
+        except: PlayerID,
+    ) -> impl Iterator<Item = (PlayerID, &Player)> {
+        self.players()
+            .filter(move |(id, player)| *id != except && player.at == room)
+    }
+
+    /// Sends a message to everyone in a room except `except`
+    fn tell_room(&self, room: RoomID, except: PlayerID, text: String) -> Vec<Message> {
+        self.players_in(room, except)
+            .map(|(to, _)| Message {
+                to,
+                text: text.clone(),
+            })
+            .collect()
+    }
+}
+
+/// What's said, if the line starts with SAY in any case
+fn speech(input: &str) -> Option<&str> {
+    let (verb, rest) = input.split_at(input.find(char::is_whitespace).unwrap_or(input.len()));
+    if verb.eq_ignore_ascii_case("say") {
+        Some(rest.trim())
+    } else {
+        None
+    }
+}
+
+fn object_names(objects: &[Object]) -> Vec<&'static str> {
+    objects.iter().map(|object| object.name).collect()
+}
@@ -1,8 +1,12 @@
 use crate::command::Command;
+use crate::player::Player;
+use crate::world::{Entity, Location, World};
 
-const HELPER_WORDS: [&str; 7] = ["a", "an", "the", "at", "to", "go", "of"];
-const PREPOSITIONS: [&str; 4] = ["in", "into", "for", "inside"];
+const HELPER_WORDS: [&str; 6] = ["a", "an", "the", "at", "go", "of"];
+const PREPOSITIONS: [&str; 6] = ["in", "into", "for", "inside", "about", "to"];
 const DIRECTIONS: [&str; 8] = ["north", "n", "south", "s", "east", "e", "west", "w"];
+// verbs about things lying around, so the room is searched before the player's pockets
+const ROOM_FIRST: [&str; 3] = ["take", "pickup", "get"];
 
 #[derive(Clone, Debug)]
 enum Token {
@@ -27,6 +31,41 @@ impl Parser {
         }
     }
 
+    /// Works out what the names in a command refer to, from where the player stands:
+    /// something they carry, something in the room, someone in the room, or a way out
+    pub fn resolve(command: &mut Command, player: &Player, world: &World) {
+        let room_first =
+            matches!(command.verb.as_deref(), Some(verb) if ROOM_FIRST.contains(&verb));
+        command.target = command
+            .obj
+            .as_deref()
+            .and_then(|name| Parser::resolve_name(name, room_first, player, world));
+        command.target_prep = command
+            .obj_prep
+            .as_deref()
+            .and_then(|name| Parser::resolve_name(name, false, player, world));
+    }
+
+    fn resolve_name(
+        name: &str,
+        room_first: bool,
+        player: &Player,
+        world: &World,
+    ) -> Option<Entity> {
+        let here = player.at;
+        let carried = world.object_named(Location::Player(player.id), name);
+        let lying_around = world.object_named(Location::Room(here), name);
+        let object = if room_first {
+            lying_around.or(carried)
+        } else {
+            carried.or(lying_around)
+        };
+        object
+            .map(Entity::Object)
+            .or_else(|| world.character_named(here, name).map(Entity::Character))
+            .or_else(|| world.door_towards(here, name).map(Entity::Door))
+    }
+
     fn clean(input: &str) -> Vec<String> {
         input
             .split_whitespace()
@@ -78,6 +117,8 @@ impl Parser {
                 obj: None,
                 prep: None,
                 obj_prep: None,
+                target: None,
+                target_prep: None,
             };
             let token = &tokens[0];
             match token {
@@ -98,6 +139,10 @@ impl Parser {
                 cmd_tokens.obj = Some(value.to_string());
                 Parser::parse_obj(tokens[1..].to_vec(), cmd_tokens)
             }
+            // e.g. "talk to cat"
+            Token::Preposition { value } if value == "to" => {
+                Parser::parse_verb(tokens[1..].to_vec(), cmd_tokens)
+            }
             Token::EOF => Some(cmd_tokens.clone()),
             _ => None,
         }
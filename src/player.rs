@@ -2,13 +2,22 @@ use crate::entity::Object;
 use crate::inventory::Inventory;
 use crate::room::Room;
 use crate::room::RoomID;
+use crate::world::PlayerID;
+use std::collections::HashMap;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Player {
+    pub id: PlayerID,
     pub name: &'static str,
     pub desc: &'static str,
     pub at: RoomID,
     pub inventory: Inventory,
+    /// how many times the player has entered each room
+    pub visits: HashMap<RoomID, u32>,
+    /// where RETURN goes: the room the last move or trip started from
+    pub came_from: Option<RoomID>,
+    /// who was in each room, besides anyone following, the last time the player was there
+    pub seen: HashMap<RoomID, Vec<&'static str>>,
 }
 
 impl Player {
@@ -24,7 +33,39 @@ impl Player {
     }
 
     pub fn go(&mut self, room_id: RoomID) {
+        // the room the game starts in was never entered, but it has been visited
+        self.visits.entry(self.at).or_insert(1);
+        self.came_from = Some(self.at);
         self.at = room_id;
+        *self.visits.entry(room_id).or_insert(0) += 1;
+    }
+
+    /// Notes who is in the player's room, besides anyone following them.
+    /// Returns whether anyone is here who wasn't when the player last saw the room.
+    pub fn look_around(&mut self, rooms: &[Room]) -> bool {
+        let mut here: Vec<&'static str> = self
+            .get_curr_room(rooms)
+            .characters
+            .iter()
+            .filter(|character| !character.is_following)
+            .map(|character| character.name)
+            .collect();
+        here.sort_unstable();
+        let before = self.seen.insert(self.at, here.clone()).unwrap_or_default();
+        here.iter().any(|name| !before.contains(name))
+    }
+
+    pub fn has_visited(&self, room_id: RoomID) -> bool {
+        self.visits(room_id) > 0
+    }
+
+    /// How many times the player has been in a room, counting the current visit
+    pub fn visits(&self, room_id: RoomID) -> u32 {
+        match self.visits.get(&room_id) {
+            Some(visits) => *visits,
+            None if room_id == self.at => 1,
+            None => 0,
+        }
     }
 
     pub fn list_objects(&self) -> String {
@@ -47,10 +88,6 @@ impl Player {
         self.inventory.find_object(object_name)
     }
 
-    pub fn find_object_mut(&mut self, object_name: &str) -> Option<&mut Object> {
-        self.inventory.find_object_mut(object_name)
-    }
-
     pub fn take_object(&mut self, object: Object) {
         self.inventory.add(object);
     }
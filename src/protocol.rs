@@ -0,0 +1,85 @@
+use crate::entity::Object;
+use crate::game::{Ending, Game};
+use crate::util::watch_time;
+use serde_json::{json, Value};
+use std::io::{self, BufRead, Write};
+
+/// Everything a client can see right now, without any prose to scrape
+pub fn observe(game: &Game) -> Value {
+    let here = game.player.get_curr_room(&game.rooms);
+    let elapsed = game.state.clock.elapsed();
+    json!({
+        "room": {
+            "name": here.name,
+            "description": here.written(&game.player, &game.rooms, &game.state),
+        },
+        // anything inside a container is listed with that container instead
+        "objects": here.top_level_objects().map(describe_object).collect::<Vec<_>>(),
+        "characters": here.characters.iter().map(|c| c.name).collect::<Vec<_>>(),
+        "exits": here
+            .doors
+            .iter()
+            .map(|door| json!({ "direction": door.direction, "open": door.is_open }))
+            .collect::<Vec<_>>(),
+        "inventory": game.player.inventory.objects.iter().map(describe_object).collect::<Vec<_>>(),
+        "time": {
+            "elapsed": elapsed.as_secs(),
+            "watch": watch_time(elapsed),
+        },
+        "ending": match game.ending() {
+            Some(Ending::Won) => Some("won"),
+            Some(Ending::Lost) => Some("lost"),
+            None => None,
+        },
+    })
+}
+
+fn describe_object(object: &Object) -> Value {
+    json!({ "name": object.name, "contents": object.inventory })
+}
+
+/// Plays one request and builds the response. A request is either a bare string
+/// (`"take watch"`) or an object with a `command` (`{"command": "take watch"}`);
+/// an object without one just observes the world.
+pub fn respond(game: &mut Game, line: &str) -> Value {
+    let request: Value = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(e) => return json!({ "error": format!("invalid JSON: {}", e) }),
+    };
+    let command = match &request {
+        Value::String(command) => Some(command.as_str()),
+        Value::Object(fields) => match fields.get("command") {
+            Some(Value::String(command)) => Some(command.as_str()),
+            Some(_) => return json!({ "error": "`command` must be a string" }),
+            None => None,
+        },
+        _ => return json!({ "error": "expected a string or an object" }),
+    };
+    let mut messages = vec![];
+    if let Some(command) = command {
+        messages = game.step(command);
+        // there's no pacing over JSON, so scenes arrive as plain text
+        messages.extend(game.cued_scenes().iter().map(|scene| scene.text()));
+    }
+    let mut response = observe(game);
+    response["messages"] = json!(messages);
+    response
+}
+
+/// Speaks the JSON-lines protocol: one request per line in, one response per line out.
+/// The first response is sent before any input and carries the opening room description.
+pub fn run<R: BufRead, W: Write>(game: &mut Game, input: R, mut output: W) -> io::Result<()> {
+    let mut opening = observe(game);
+    opening["messages"] = json!([game.describe_room()]);
+    writeln!(output, "{}", opening)?;
+    output.flush()?;
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        writeln!(output, "{}", respond(game, &line))?;
+        output.flush()?;
+    }
+    Ok(())
+}
@@ -1,15 +1,24 @@
 use crate::character::Character;
+use crate::description::{render, Variant};
 use crate::door::Door;
 use crate::entity::Object;
+use crate::game_state::GameState;
 use crate::inventory::Inventory;
-use crate::util::get_room_name_border;
+use crate::player::Player;
+use crate::util::{capitalize, english_list, get_room_name_border};
+use crate::world::{CharacterID, DoorID};
 
+#[derive(Clone)]
 pub struct Room {
     pub name: &'static str,
     pub desc: &'static str,
+    /// see `Variant`
+    pub variants: Vec<Variant>,
     pub doors: Vec<Door>,
     pub inventory: Inventory,
     pub characters: Vec<Character>,
+    /// sound file looped while the player is here
+    pub ambience: Option<&'static str>,
 }
 
 impl Room {
@@ -18,10 +27,77 @@ impl Room {
         format!("{}\n{}\n{}", border, self.name, border)
     }
 
+    /// The written description, then whatever is lying around, who is here, and the ways out
+    pub fn describe(&self, player: &Player, rooms: &[Room], state: &GameState) -> String {
+        self.written(player, rooms, state) + self.details().as_str()
+    }
+
+    /// The written description, or the variant that applies now.
+    /// `{contents}` in it lists what is lying around.
+    pub fn written(&self, player: &Player, rooms: &[Room], state: &GameState) -> String {
+        let loose: Vec<String> = self.loose_objects().map(|o| o.name.to_owned()).collect();
+        render(self.desc, &self.variants, &loose, player, rooms, state)
+    }
+
+    /// Whatever is lying around, who is here, and the ways out, without the written description
+    pub fn details(&self) -> String {
+        let mut desc = String::new();
+        let objects: Vec<String> = self
+            .loose_objects()
+            .map(|object| format!("the {}", object.name))
+            .collect();
+        if !objects.is_empty() {
+            desc += &format!(" You see {} here.", english_list(&objects));
+        }
+        let characters: Vec<String> = self
+            .characters
+            .iter()
+            .map(|character| format!("the {}", character.name))
+            .collect();
+        if !characters.is_empty() {
+            let verb = if characters.len() == 1 { "is" } else { "are" };
+            desc += &format!(" {} {} here.", capitalize(&english_list(&characters)), verb);
+        }
+        let exits: Vec<String> = self
+            .doors
+            .iter()
+            .map(|door| match door.is_open {
+                true => door.direction.to_owned(),
+                false => format!("{} (closed)", door.direction),
+            })
+            .collect();
+        if !exits.is_empty() {
+            desc += &format!("\nExits: {}.", exits.join(", "));
+        }
+        desc
+    }
+
+    /// Objects that aren't inside anything else, scenery included
+    pub fn top_level_objects(&self) -> impl Iterator<Item = &Object> {
+        let objects = &self.inventory.objects;
+        objects.iter().filter(move |object| {
+            let name = object.name.to_owned();
+            !objects.iter().any(|other| other.inventory.contains(&name))
+        })
+    }
+
+    /// Objects that aren't scenery and aren't inside anything else
+    pub fn loose_objects(&self) -> impl Iterator<Item = &Object> {
+        self.top_level_objects().filter(|object| !object.scenery)
+    }
+
     pub fn get_door(&self, direction: &str) -> Option<&Door> {
         self.doors.iter().find(|door| door.direction == direction)
     }
 
+    pub fn door(&self, id: DoorID) -> Option<&Door> {
+        self.doors.iter().find(|door| door.id == id)
+    }
+
+    pub fn door_mut(&mut self, id: DoorID) -> Option<&mut Door> {
+        self.doors.iter_mut().find(|door| door.id == id)
+    }
+
     /// -----------------------------
     /// methods for inventory/objects
     /// -----------------------------
@@ -33,10 +109,6 @@ impl Room {
         self.inventory.find_object(name)
     }
 
-    pub fn find_object_mut(&mut self, name: &str) -> Option<&mut Object> {
-        self.inventory.find_object_mut(name)
-    }
-
     pub fn remove(&mut self, name: &str) -> Option<Object> {
         self.inventory.remove(name)
     }
@@ -44,18 +116,22 @@ impl Room {
     /// ----------------------
     /// methods for characters
     /// ----------------------
-    pub fn find_character(&self, name: &str) -> Option<&Character> {
-        self.characters
-            .iter()
-            .find(|character| character.name == name)
+    pub fn character(&self, id: CharacterID) -> Option<&Character> {
+        self.characters.iter().find(|character| character.id == id)
     }
 
-    pub fn find_character_mut(&mut self, name: &str) -> Option<&mut Character> {
+    pub fn character_mut(&mut self, id: CharacterID) -> Option<&mut Character> {
         self.characters
             .iter_mut()
+            .find(|character| character.id == id)
+    }
+
+    pub fn find_character(&self, name: &str) -> Option<&Character> {
+        self.characters
+            .iter()
             .find(|character| character.name == name)
     }
 }
 
-#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
 pub struct RoomID(pub usize);
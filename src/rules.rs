@@ -0,0 +1,131 @@
+//! Rules that change what a verb does without touching its handler, in the style of
+//! Inform's rulebooks. Each rule says when it applies (a verb, the object or character
+//! the verb is used on, a room and a condition, any of which can be left out) and what
+//! happens when it does.
+//!
+//! - `before` rules run first. They can add text, change the world, stop the action
+//!   (`veto`) or play another command in its place (`redirect`).
+//! - `instead` rules replace the action. Only the first that applies runs.
+//! - `after` rules run once the action has happened, to add text or side effects.
+//!
+//! Within a phase the more specific rules go first: a rule counts one for each of verb,
+//! object, room and condition it sets. Rules that are equally specific keep the order
+//! they were written in. Every rule that fires is noted in `GameState::rules_fired`.
+
+use crate::condition::Condition;
+use crate::dialogue::Effect;
+use crate::game_state::GameState;
+use crate::player::Player;
+use crate::room::{Room, RoomID};
+use crate::world::World;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Before,
+    Instead,
+    After,
+}
+
+#[derive(Debug, Clone)]
+pub struct Rule {
+    /// shown when tracing which rules fired
+    pub name: &'static str,
+    pub phase: Phase,
+    /// the full name of the verb, as in `VERBS`
+    pub verb: Option<&'static str>,
+    /// the object or character the verb is used on, as the player named it if it isn't in reach
+    pub object: Option<&'static str>,
+    pub room: Option<RoomID>,
+    pub when: Option<Condition>,
+    pub text: Option<&'static str>,
+    /// applied on behalf of the rule's object, so character effects work on rules about characters
+    pub effects: Vec<Effect>,
+    /// for `before` rules: the action doesn't happen
+    pub veto: bool,
+    /// for `before` rules: this command is played instead
+    pub redirect: Option<&'static str>,
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Rule {
+            name: "",
+            phase: Phase::Before,
+            verb: None,
+            object: None,
+            room: None,
+            when: None,
+            text: None,
+            effects: vec![],
+            veto: false,
+            redirect: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Rulebook {
+    pub rules: Vec<Rule>,
+}
+
+impl Rule {
+    pub fn applies(
+        &self,
+        verb: &str,
+        object: Option<&str>,
+        player: &Player,
+        rooms: &[Room],
+        state: &GameState,
+    ) -> bool {
+        self.verb.is_none_or(|v| v == verb)
+            && self.object.is_none_or(|name| object == Some(name))
+            && self.room.is_none_or(|room| room == player.at)
+            && self
+                .when
+                .as_ref()
+                .is_none_or(|when| when.holds(player, rooms, state))
+    }
+
+    fn specificity(&self) -> usize {
+        [
+            self.verb.is_some(),
+            self.object.is_some(),
+            self.room.is_some(),
+            self.when.is_some(),
+        ]
+        .iter()
+        .filter(|set| **set)
+        .count()
+    }
+
+    /// Applies the rule's effects, notes that it fired, and returns what there is to say
+    pub fn fire(
+        &self,
+        player: &mut Player,
+        rooms: &mut [Room],
+        world: &mut World,
+        state: &mut GameState,
+    ) -> Vec<String> {
+        state.rules_fired.push(self.name);
+        let mut messages: Vec<String> = self.text.iter().map(|text| text.to_string()).collect();
+        for effect in &self.effects {
+            let character = self.object.unwrap_or_default();
+            messages.extend(effect.apply(character, player, rooms, world, state));
+        }
+        messages
+    }
+}
+
+impl Rulebook {
+    /// The rules in a phase, in the order they are considered
+    pub fn phase(&self, phase: Phase) -> Vec<&Rule> {
+        let mut rules: Vec<&Rule> = self
+            .rules
+            .iter()
+            .filter(|rule| rule.phase == phase)
+            .collect();
+        // a stable sort keeps ties in the order they were written
+        rules.sort_by_key(|rule| std::cmp::Reverse(rule.specificity()));
+        rules
+    }
+}
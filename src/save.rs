@@ -0,0 +1,64 @@
+//! Saved games. A save is the list of commands played and when, so loading one
+//! replays them against a fresh copy of the world and nothing in the world needs
+//! to know how to write itself down. The story's variables are written down as well,
+//! so a save can be read on its own.
+
+use crate::game::Game;
+use crate::vars::Vars;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Save {
+    /// the world file played, or `None` for the built-in world
+    pub world: Option<String>,
+    pub seed: u64,
+    /// game time when the game was saved
+    pub elapsed: u64,
+    /// each command with the game time it was played at
+    pub commands: Vec<(u64, String)>,
+    /// the story's variables when the game was saved; older saves don't have them
+    #[serde(default)]
+    pub vars: Option<Vars>,
+}
+
+impl Save {
+    pub fn of(game: &Game, world: Option<String>) -> Self {
+        Save {
+            world,
+            seed: game.state.seed,
+            elapsed: game.state.clock.elapsed_secs(),
+            commands: game.log.clone(),
+            vars: Some(game.state.vars.clone()),
+        }
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        let text = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, text + "\n").map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        serde_json::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Plays the saved commands into a freshly made `game`, leaving it where the save left off
+    pub fn replay(&self, game: &mut Game) {
+        game.state.reseed(self.seed);
+        // debug commands only come back if the game they're replayed into allows them
+        for (time, command) in &self.commands {
+            game.state.clock.set_elapsed(Duration::from_secs(*time));
+            game.step(command);
+        }
+        game.state
+            .clock
+            .set_elapsed(Duration::from_secs(self.elapsed));
+        // anything set outside of play, say from a debug command, comes back too
+        if let Some(vars) = &self.vars {
+            game.state.vars = vars.clone();
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use crate::player::Player;
+use crate::room::{Room, RoomID};
+use crate::world::World;
+
+/// A place a character should be at a given game time (in seconds since the start)
+#[derive(Debug, Clone)]
+pub struct Stop {
+    pub time: u64,
+    pub room: RoomID,
+    pub msg_on_depart: Option<&'static str>,
+    pub msg_on_arrive: Option<&'static str>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Schedule {
+    pub stops: Vec<Stop>,
+    /// index of the next stop that hasn't been reached yet
+    pub next: usize,
+}
+
+impl Schedule {
+    /// Walks a path of rooms, leaving `start` and taking `step` seconds per room
+    pub fn along(start: u64, step: u64, path: &[RoomID]) -> Self {
+        Schedule {
+            stops: path
+                .iter()
+                .enumerate()
+                .map(|(i, room)| Stop {
+                    time: start + step * i as u64,
+                    room: *room,
+                    msg_on_depart: None,
+                    msg_on_arrive: None,
+                })
+                .collect(),
+            next: 0,
+        }
+    }
+
+    fn due(&self, elapsed: u64) -> Option<&Stop> {
+        self.stops
+            .get(self.next)
+            .filter(|stop| stop.time <= elapsed)
+    }
+}
+
+/// Moves every character whose next stop is due. Returns the arrival and
+/// departure messages that the player can see from where they are standing.
+pub fn update_schedules(
+    player: &Player,
+    rooms: &mut [Room],
+    world: &mut World,
+    elapsed: u64,
+) -> Vec<String> {
+    let mut messages = vec![];
+    // stops missed while following the player are skipped, not made up for later
+    for character in rooms.iter_mut().flat_map(|room| room.characters.iter_mut()) {
+        if character.is_following {
+            while character.schedule.due(elapsed).is_some() {
+                character.schedule.next += 1;
+            }
+        }
+    }
+    loop {
+        let due = rooms.iter().enumerate().find_map(|(i, room)| {
+            room.characters.iter().find_map(|character| {
+                // characters following the player ignore their schedule
+                if character.is_following {
+                    return None;
+                }
+                character
+                    .schedule
+                    .due(elapsed)
+                    .map(|stop| (character.id, character.name, RoomID(i), stop.clone()))
+            })
+        });
+        let (id, name, from, stop) = match due {
+            Some(due) => due,
+            None => break,
+        };
+
+        rooms[from.0].character_mut(id).unwrap().schedule.next += 1;
+        world.move_character(id, stop.room, rooms);
+        if from == stop.room {
+            continue;
+        }
+
+        if player.at == from {
+            messages.push(match stop.msg_on_depart {
+                Some(msg) => msg.to_owned(),
+                None => match rooms[from.0].doors.iter().find(|d| d.target == stop.room) {
+                    Some(door) => format!("The {} leaves to the {}.", name, door.direction),
+                    None => format!("The {} leaves.", name),
+                },
+            });
+        } else if player.at == stop.room {
+            messages.push(match stop.msg_on_arrive {
+                Some(msg) => msg.to_owned(),
+                None => match rooms[stop.room.0].doors.iter().find(|d| d.target == from) {
+                    Some(door) => format!("The {} arrives from the {}.", name, door.direction),
+                    None => format!("The {} arrives.", name),
+                },
+            });
+        }
+    }
+    messages
+}
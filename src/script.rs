@@ -0,0 +1,502 @@
+//! Scripts for behaviour that doesn't fit the declarative world format. World files
+//! attach them to hooks, and they are written in Rhai (https://rhai.rs).
+//!
+//! - `before <verb>` and `after <verb>` on an object or character run around the
+//!   verb being used on it. A `before` script can call `stop()` to cancel the verb.
+//! - `talk` on a character runs after the player talks to them.
+//! - `enter` and `leave` on a room run as the player comes and goes.
+//! - timers run once, as soon as the game time reaches theirs.
+//!
+//! Scripts can't reach the file system or the network, and they are stopped if they
+//! run too long. Everything they can do to the world is in `engine`. A script that
+//! fails leaves the world the way it found it, and the player is told where it was written.
+
+use crate::entity::Object;
+use crate::game_state::GameState;
+use crate::player::Player;
+use crate::room::{Room, RoomID};
+use crate::util::intern;
+use crate::vars::Value;
+use crate::world::{Location, ObjectID, World};
+use rhai::{Array, Dynamic, Engine, AST};
+use std::sync::{Arc, Mutex};
+use std::{iter, mem};
+
+// enough for any sensible hook, and a quick end to an endless loop
+const MAX_OPERATIONS: u64 = 100_000;
+const MAX_CALL_LEVELS: usize = 32;
+const MAX_STRING_SIZE: usize = 10_000;
+const MAX_ARRAY_SIZE: usize = 1_000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Hook {
+    /// a verb, by its full name, and the name of the object or character it's used on
+    Before(&'static str, &'static str),
+    After(&'static str, &'static str),
+    Enter(RoomID),
+    Leave(RoomID),
+    /// game time, in seconds
+    Timer(u64),
+}
+
+#[derive(Clone)]
+pub struct Script {
+    pub hook: Hook,
+    /// where the script was written, for error messages
+    pub location: String,
+    ast: AST,
+}
+
+/// What running the scripts on a hook had to say
+#[derive(Debug, Default)]
+pub struct Outcome {
+    pub messages: Vec<String>,
+    /// whether a `before` script cancelled the verb
+    pub stopped: bool,
+}
+
+/// A world's scripts, and the engine they run on. Copies of a game share the engine,
+/// and take turns with it.
+#[derive(Clone, Default)]
+pub struct Scripts {
+    pub scripts: Vec<Script>,
+    /// built the first time a script runs
+    runner: Arc<Mutex<Option<Runner>>>,
+}
+
+struct Runner {
+    engine: Engine,
+    sandbox: Arc<Mutex<Sandbox>>,
+}
+
+/// The world as a script sees it. The game's world is swapped in for each run and back
+/// out afterwards, leaving the world from the last run here until the next one.
+struct Sandbox {
+    player: Player,
+    rooms: Vec<Room>,
+    world: World,
+    state: GameState,
+    output: Vec<String>,
+    stopped: bool,
+    /// the state and the places as they were before the running script first changed
+    /// them, to put back if it fails
+    saved_state: Option<GameState>,
+    saved_places: Option<(Player, Vec<Room>, World)>,
+}
+
+impl Script {
+    /// Errors give the location, and the line and position in the script
+    pub fn compile(hook: Hook, source: &str, location: String) -> Result<Script, String> {
+        match limited(Engine::new()).compile(source) {
+            Ok(ast) => Ok(Script {
+                hook,
+                location,
+                ast,
+            }),
+            Err(e) => Err(format!("{}: {}", location, e)),
+        }
+    }
+}
+
+impl Sandbox {
+    fn new(player: &Player, rooms: &[Room], world: &World, state: &GameState) -> Self {
+        Sandbox {
+            player: player.clone(),
+            rooms: rooms.to_vec(),
+            world: world.clone(),
+            state: state.clone(),
+            output: vec![],
+            stopped: false,
+            saved_state: None,
+            saved_places: None,
+        }
+    }
+
+    /// Trades places with the game's world
+    fn swap(
+        &mut self,
+        player: &mut Player,
+        rooms: &mut [Room],
+        world: &mut World,
+        state: &mut GameState,
+    ) {
+        if self.rooms.len() != rooms.len() {
+            // left over from a copy of the game with other rooms
+            self.rooms = rooms.to_vec();
+        }
+        mem::swap(&mut self.player, player);
+        self.rooms.swap_with_slice(rooms);
+        mem::swap(&mut self.world, world);
+        mem::swap(&mut self.state, state);
+    }
+
+    /// The state, copied first if this is the script's first change to it
+    fn state_mut(&mut self) -> &mut GameState {
+        if self.saved_state.is_none() {
+            self.saved_state = Some(self.state.clone());
+        }
+        &mut self.state
+    }
+
+    /// Copies the player, rooms and world before the script's first change to them
+    fn save_places(&mut self) {
+        if self.saved_places.is_none() {
+            let places = (self.player.clone(), self.rooms.clone(), self.world.clone());
+            self.saved_places = Some(places);
+        }
+    }
+
+    /// Puts back anything the script changed
+    fn restore(&mut self) {
+        if let Some(state) = self.saved_state.take() {
+            self.state = state;
+        }
+        if let Some((player, rooms, world)) = self.saved_places.take() {
+            self.player = player;
+            self.rooms = rooms;
+            self.world = world;
+        }
+    }
+}
+
+impl Scripts {
+    pub fn new(scripts: Vec<Script>) -> Self {
+        Scripts {
+            scripts,
+            runner: Arc::default(),
+        }
+    }
+
+    /// Runs one script against the world. Returns what it printed and whether it
+    /// called `stop()`, or an error message for the player.
+    fn run_script(
+        &self,
+        script: &Script,
+        player: &mut Player,
+        rooms: &mut [Room],
+        world: &mut World,
+        state: &mut GameState,
+    ) -> Result<(Vec<String>, bool), String> {
+        let mut runner = self.runner.lock().unwrap();
+        let runner = runner.get_or_insert_with(|| {
+            let sandbox = Arc::new(Mutex::new(Sandbox::new(player, rooms, world, state)));
+            Runner {
+                engine: engine(&sandbox),
+                sandbox,
+            }
+        });
+        runner
+            .sandbox
+            .lock()
+            .unwrap()
+            .swap(player, rooms, world, state);
+        let result = runner.engine.run_ast(&script.ast);
+        let mut sandbox = runner.sandbox.lock().unwrap();
+        if result.is_err() {
+            sandbox.restore();
+        }
+        sandbox.saved_state = None;
+        sandbox.saved_places = None;
+        sandbox.swap(player, rooms, world, state);
+        let output = mem::take(&mut sandbox.output);
+        let stopped = mem::replace(&mut sandbox.stopped, false);
+        match result {
+            Ok(()) => Ok((output, stopped)),
+            Err(e) => Err(format!("[Script error at {}: {}]", script.location, e)),
+        }
+    }
+
+    /// Runs every script on hooks that match, in the order they were written
+    pub fn run<F: Fn(&Hook) -> bool>(
+        &self,
+        matches: F,
+        player: &mut Player,
+        rooms: &mut [Room],
+        world: &mut World,
+        state: &mut GameState,
+    ) -> Outcome {
+        let mut outcome = Outcome::default();
+        for script in self.scripts.iter().filter(|script| matches(&script.hook)) {
+            match self.run_script(script, player, rooms, world, state) {
+                Ok((output, stopped)) => {
+                    outcome.messages.extend(output);
+                    outcome.stopped |= stopped;
+                }
+                Err(e) => outcome.messages.push(e),
+            }
+        }
+        outcome
+    }
+
+    /// Runs the timers whose time has come and that haven't run yet
+    pub fn run_timers(
+        &self,
+        player: &mut Player,
+        rooms: &mut [Room],
+        world: &mut World,
+        state: &mut GameState,
+    ) -> Vec<String> {
+        let elapsed = state.clock.elapsed_secs();
+        let due: Vec<usize> = self
+            .scripts
+            .iter()
+            .enumerate()
+            .filter(|(i, script)| {
+                matches!(script.hook, Hook::Timer(time) if time <= elapsed)
+                    && !state.timers_fired.contains(i)
+            })
+            .map(|(i, _)| i)
+            .collect();
+        let mut messages = vec![];
+        for i in due {
+            state.timers_fired.push(i);
+            match self.run_script(&self.scripts[i], player, rooms, world, state) {
+                Ok((output, _)) => messages.extend(output),
+                Err(e) => messages.push(e),
+            }
+        }
+        messages
+    }
+}
+
+fn limited(mut engine: Engine) -> Engine {
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.set_max_call_levels(MAX_CALL_LEVELS);
+    engine.set_max_string_size(MAX_STRING_SIZE);
+    engine.set_max_array_size(MAX_ARRAY_SIZE);
+    engine
+}
+
+/// The functions scripts can call. Objects are found among what the player carries
+/// and what's in the room they're in.
+fn engine(sandbox: &Arc<Mutex<Sandbox>>) -> Engine {
+    let mut engine = limited(Engine::new());
+
+    let s = sandbox.clone();
+    engine.on_print(move |text| s.lock().unwrap().output.push(text.to_owned()));
+    // the default would write over the screen
+    engine.on_debug(|_, _, _| {});
+    let s = sandbox.clone();
+    engine.register_fn("say", move |text: &str| {
+        s.lock().unwrap().output.push(text.to_owned())
+    });
+    let s = sandbox.clone();
+    engine.register_fn("stop", move || s.lock().unwrap().stopped = true);
+
+    // variables, flags, time and sound
+    let s = sandbox.clone();
+    engine.register_fn("get_var", move |name: &str| {
+        match s.lock().unwrap().state.vars.get(name) {
+            Some(Value::Bool(value)) => Dynamic::from(*value),
+            Some(Value::Int(value)) => Dynamic::from(*value),
+            Some(Value::Str(value)) => Dynamic::from(value.clone()),
+            Some(Value::Time(secs)) => Dynamic::from(*secs as i64),
+            None => Dynamic::UNIT,
+        }
+    });
+    let s = sandbox.clone();
+    engine.register_fn("set_var", move |name: &str, value: bool| {
+        s.lock()
+            .unwrap()
+            .state_mut()
+            .vars
+            .set(name, Value::Bool(value))
+    });
+    let s = sandbox.clone();
+    engine.register_fn("set_var", move |name: &str, value: i64| {
+        s.lock()
+            .unwrap()
+            .state_mut()
+            .vars
+            .set(name, Value::Int(value))
+    });
+    let s = sandbox.clone();
+    engine.register_fn("set_var", move |name: &str, value: &str| {
+        s.lock()
+            .unwrap()
+            .state_mut()
+            .vars
+            .set(name, Value::Str(value.to_owned()))
+    });
+    let s = sandbox.clone();
+    engine.register_fn("add_var", move |name: &str, by: i64| {
+        s.lock().unwrap().state_mut().vars.add(name, by)
+    });
+    let s = sandbox.clone();
+    engine.register_fn("mark_time", move |name: &str| {
+        let s = &mut *s.lock().unwrap();
+        let state = s.state_mut();
+        let now = state.clock.elapsed_secs();
+        state.vars.set(name, Value::Time(now));
+    });
+    let s = sandbox.clone();
+    engine.register_fn("has_flag", move |flag: &str| {
+        s.lock().unwrap().state.has_flag(flag)
+    });
+    let s = sandbox.clone();
+    engine.register_fn("set_flag", move |flag: &str| {
+        s.lock().unwrap().state_mut().set_flag(flag)
+    });
+    let s = sandbox.clone();
+    engine.register_fn("clear_flag", move |flag: &str| {
+        s.lock().unwrap().state_mut().clear_flag(flag)
+    });
+    let s = sandbox.clone();
+    engine.register_fn("time", move || {
+        s.lock().unwrap().state.clock.elapsed_secs() as i64
+    });
+    let s = sandbox.clone();
+    engine.register_fn("random", move |below: i64| {
+        s.lock().unwrap().state_mut().random(below.max(0) as u64) as i64
+    });
+    let s = sandbox.clone();
+    engine.register_fn("play_sound", move |sound: &str| {
+        s.lock().unwrap().state_mut().play_sound(intern(sound))
+    });
+
+    // the player and where they are
+    let s = sandbox.clone();
+    engine.register_fn("here", move || {
+        let s = s.lock().unwrap();
+        s.player.get_curr_room(&s.rooms).name.to_owned()
+    });
+    let s = sandbox.clone();
+    engine.register_fn("move_player", move |name: &str| {
+        let s = &mut *s.lock().unwrap();
+        s.save_places();
+        match s
+            .rooms
+            .iter()
+            .position(|room| room.name.eq_ignore_ascii_case(name))
+        {
+            Some(i) => {
+                s.player.go(RoomID(i));
+                true
+            }
+            None => false,
+        }
+    });
+    let s = sandbox.clone();
+    engine.register_fn("player_has", move |name: &str| {
+        s.lock().unwrap().player.has(name)
+    });
+
+    // objects
+    let s = sandbox.clone();
+    engine.register_fn("give_player", move |name: &str| {
+        let s = &mut *s.lock().unwrap();
+        s.save_places();
+        let here = s.player.get_curr_room(&s.rooms);
+        let held = here
+            .characters
+            .iter()
+            .map(|character| Location::Character(character.id));
+        let object = iter::once(Location::Room(s.player.at))
+            .chain(held)
+            .find_map(|at| s.world.object_named(at, name));
+        match object {
+            Some(id) => {
+                let to = Location::Player(s.player.id);
+                s.world.move_object(id, to, &mut s.player, &mut s.rooms)
+            }
+            None => false,
+        }
+    });
+    let s = sandbox.clone();
+    engine.register_fn("spawn", move |name: &str, desc: &str| {
+        let s = &mut *s.lock().unwrap();
+        s.save_places();
+        let object = Object {
+            name: intern(name),
+            desc: intern(desc),
+            can_take: true,
+            ..Default::default()
+        };
+        let here = Location::Room(s.player.at);
+        s.world
+            .add_object(object, here, &mut s.player, &mut s.rooms);
+    });
+    let s = sandbox.clone();
+    engine.register_fn("destroy", move |name: &str| {
+        let s = &mut *s.lock().unwrap();
+        s.save_places();
+        match find_object(&s.player, &s.world, name) {
+            Some(id) => s
+                .world
+                .remove_object(id, &mut s.player, &mut s.rooms)
+                .is_some(),
+            None => false,
+        }
+    });
+    let s = sandbox.clone();
+    engine.register_fn("contents", move |container: &str| -> Array {
+        let s = s.lock().unwrap();
+        let object = find_object(&s.player, &s.world, container)
+            .and_then(|id| s.world.object(id, &s.player, &s.rooms));
+        match object {
+            Some(object) => object
+                .inventory
+                .iter()
+                .cloned()
+                .map(Dynamic::from)
+                .collect(),
+            None => Array::new(),
+        }
+    });
+    let s = sandbox.clone();
+    engine.register_fn("put_inside", move |container: &str, content: &str| {
+        let s = &mut *s.lock().unwrap();
+        s.save_places();
+        let object = find_object(&s.player, &s.world, container)
+            .and_then(|id| s.world.object_mut(id, &mut s.player, &mut s.rooms));
+        match object {
+            Some(object) => {
+                object.inventory.push(content.to_owned());
+                true
+            }
+            None => false,
+        }
+    });
+    let s = sandbox.clone();
+    engine.register_fn("empty", move |container: &str| {
+        let s = &mut *s.lock().unwrap();
+        s.save_places();
+        let object = find_object(&s.player, &s.world, container)
+            .and_then(|id| s.world.object_mut(id, &mut s.player, &mut s.rooms));
+        match object {
+            Some(object) => {
+                object.inventory.clear();
+                true
+            }
+            None => false,
+        }
+    });
+
+    // doors out of the room the player is in
+    for (function, open) in [("open_door", true), ("close_door", false)] {
+        let s = sandbox.clone();
+        engine.register_fn(function, move |direction: &str| {
+            let s = &mut *s.lock().unwrap();
+            s.save_places();
+            let door = s
+                .world
+                .door_towards(s.player.at, direction)
+                .and_then(|id| s.world.door_mut(id, &mut s.rooms));
+            match door {
+                Some(door) => {
+                    door.is_open = open;
+                    true
+                }
+                None => false,
+            }
+        });
+    }
+    engine
+}
+
+/// Something the player carries, or else something in the room they're in
+fn find_object(player: &Player, world: &World, name: &str) -> Option<ObjectID> {
+    world
+        .object_named(Location::Player(player.id), name)
+        .or_else(|| world.object_named(Location::Room(player.at), name))
+}
@@ -0,0 +1,273 @@
+use crate::clock::Clock;
+use crate::game::{Ending, Game};
+use crate::multiplayer::{Message, PlayerID, SharedWorld};
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::net::{Shutdown, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+
+// telnet control bytes
+const IAC: u8 = 255;
+const SB: u8 = 250;
+const SE: u8 = 240;
+const WILL: u8 = 251;
+const DONT: u8 = 254;
+const BACKSPACE: u8 = 8;
+const DELETE: u8 = 127;
+
+/// Serves a world over TCP. With `run`, every connection plays its own copy, with its own clock.
+pub struct Server {
+    listener: TcpListener,
+    pub new_game: fn(Clock) -> Game,
+    /// connections that stay quiet this long are closed
+    pub idle_timeout: Duration,
+}
+
+impl Server {
+    pub fn bind<A: ToSocketAddrs>(addr: A, new_game: fn(Clock) -> Game) -> io::Result<Self> {
+        Ok(Server {
+            listener: TcpListener::bind(addr)?,
+            new_game,
+            idle_timeout: Duration::from_secs(10 * 60),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<std::net::SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accepts connections forever, one thread per session
+    pub fn run(&self) -> io::Result<()> {
+        for stream in self.listener.incoming() {
+            let stream = stream?;
+            let game = (self.new_game)(Clock::realtime());
+            let idle_timeout = self.idle_timeout;
+            thread::spawn(move || {
+                let peer = stream.peer_addr().ok();
+                if let Err(e) = play_session(stream, game, idle_timeout) {
+                    eprintln!("session {:?} ended with an error: {}", peer, e);
+                }
+            });
+        }
+        Ok(())
+    }
+}
+
+/// A player in one of the worlds the server has hosted. Seats are numbered afresh in
+/// each new world, so the world's number tells an old player from a new one.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Ticket {
+    world: u64,
+    player: PlayerID,
+}
+
+/// What connections ask of the shared world
+enum Request {
+    Join(String, TcpStream, Sender<Ticket>),
+    Input(Ticket, String),
+    Leave(Ticket),
+}
+
+impl Server {
+    /// Accepts connections forever, with everyone playing in the same world.
+    /// The world lives on its own thread and plays requests in the order they arrive.
+    pub fn run_shared(&self) -> io::Result<()> {
+        let (requests, inbox) = mpsc::channel();
+        let new_game = self.new_game;
+        thread::spawn(move || host_world(new_game, inbox));
+        for stream in self.listener.incoming() {
+            let stream = stream?;
+            let requests = requests.clone();
+            let idle_timeout = self.idle_timeout;
+            thread::spawn(move || {
+                let peer = stream.peer_addr().ok();
+                if let Err(e) = join_world(stream, requests, idle_timeout) {
+                    eprintln!("session {:?} ended with an error: {}", peer, e);
+                }
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Plays every request against one world, starting a fresh one whenever a game ends.
+/// Requests left over from an earlier world are dropped.
+fn host_world(new_game: fn(Clock) -> Game, inbox: Receiver<Request>) {
+    let mut world = SharedWorld::new(new_game(Clock::realtime()));
+    let mut generation = 0;
+    let mut streams: HashMap<PlayerID, TcpStream> = HashMap::new();
+    for request in inbox {
+        let messages = match request {
+            Request::Join(name, stream, reply) => {
+                let (id, messages) = world.join(&name);
+                streams.insert(id, stream);
+                let _ = reply.send(Ticket {
+                    world: generation,
+                    player: id,
+                });
+                messages
+            }
+            Request::Input(ticket, line) if ticket.world == generation => {
+                world.step(ticket.player, &line)
+            }
+            Request::Leave(ticket) if ticket.world == generation => {
+                streams.remove(&ticket.player);
+                world.leave(ticket.player)
+            }
+            Request::Input(..) | Request::Leave(_) => continue,
+        };
+        deliver(&mut streams, messages);
+
+        if let Some(ending) = world.ending() {
+            let text = match ending {
+                Ending::Won => "[game over: won]",
+                Ending::Lost => "[game over: lost]",
+            };
+            for (_, stream) in streams.drain() {
+                let mut stream = stream;
+                let _ = send(&mut stream, &[text.to_owned()]);
+                let _ = stream.shutdown(Shutdown::Both);
+            }
+            world = SharedWorld::new(new_game(Clock::realtime()));
+            generation += 1;
+        }
+    }
+}
+
+/// Sends each player their messages, followed by a fresh prompt.
+/// Broken connections are skipped; their sessions will leave on their own.
+fn deliver(streams: &mut HashMap<PlayerID, TcpStream>, messages: Vec<Message>) {
+    let mut by_player: Vec<(PlayerID, Vec<String>)> = vec![];
+    for msg in messages {
+        match by_player.iter_mut().find(|(to, _)| *to == msg.to) {
+            Some((_, texts)) => texts.push(msg.text),
+            None => by_player.push((msg.to, vec![msg.text])),
+        }
+    }
+    for (to, texts) in by_player {
+        if let Some(stream) = streams.get_mut(&to) {
+            let _ = send(stream, &texts).and_then(|_| prompt(stream));
+        }
+    }
+}
+
+/// Asks a new connection for a name, then passes their lines on to the shared world
+fn join_world(
+    stream: TcpStream,
+    requests: Sender<Request>,
+    idle_timeout: Duration,
+) -> io::Result<()> {
+    stream.set_read_timeout(Some(idle_timeout))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream.try_clone()?;
+    send(&mut writer, &["What's your name?".to_owned()])?;
+    prompt(&mut writer)?;
+    let name = match next_line(&mut reader, &mut writer)? {
+        Some(name) if !name.is_empty() => name,
+        Some(_) => "Stranger".to_owned(),
+        None => return Ok(()),
+    };
+
+    let (reply, joined) = mpsc::channel();
+    let _ = requests.send(Request::Join(name, stream, reply));
+    let ticket = match joined.recv() {
+        Ok(ticket) => ticket,
+        Err(_) => return Ok(()),
+    };
+    while let Some(line) = next_line(&mut reader, &mut writer)? {
+        if requests.send(Request::Input(ticket, line)).is_err() {
+            break;
+        }
+    }
+    let _ = requests.send(Request::Leave(ticket));
+    Ok(())
+}
+
+/// Plays one game over a connection until the player quits, goes idle, hangs up or the game ends
+pub fn play_session(stream: TcpStream, mut game: Game, idle_timeout: Duration) -> io::Result<()> {
+    stream.set_read_timeout(Some(idle_timeout))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+    send(&mut writer, &[game.describe_room()])?;
+    loop {
+        prompt(&mut writer)?;
+        let line = match next_line(&mut reader, &mut writer)? {
+            Some(line) => line,
+            None => return Ok(()),
+        };
+        let mut messages = game.step(&line);
+        messages.extend(game.cued_scenes().iter().map(|scene| scene.text()));
+        send(&mut writer, &messages)?;
+        match game.ending() {
+            Some(Ending::Won) => return send(&mut writer, &["[game over: won]".to_owned()]),
+            Some(Ending::Lost) => return send(&mut writer, &["[game over: lost]".to_owned()]),
+            None => {}
+        }
+    }
+}
+
+/// Reads the next line from a client. Returns `None` once the client quits,
+/// hangs up or goes idle, after saying goodbye if they're still listening.
+fn next_line<R: BufRead, W: Write>(reader: &mut R, writer: &mut W) -> io::Result<Option<String>> {
+    let mut raw = vec![];
+    match reader.read_until(b'\n', &mut raw) {
+        // the client hung up
+        Ok(0) => return Ok(None),
+        Ok(_) => {}
+        Err(e) if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::TimedOut => {
+            send(
+                writer,
+                &["\r\nYou nod off. (Disconnected for inactivity.)".to_owned()],
+            )?;
+            return Ok(None);
+        }
+        Err(e) => return Err(e),
+    }
+    let line = clean_line(&raw);
+    if line.eq_ignore_ascii_case("quit") {
+        send(writer, &["Goodbye!".to_owned()])?;
+        return Ok(None);
+    }
+    Ok(Some(line))
+}
+
+fn prompt<W: Write>(writer: &mut W) -> io::Result<()> {
+    write!(writer, "\r\n> ")?;
+    writer.flush()
+}
+
+/// Writes messages with telnet line endings
+fn send<W: Write>(writer: &mut W, messages: &[String]) -> io::Result<()> {
+    for msg in messages {
+        write!(writer, "{}\r\n", msg.replace('\n', "\r\n"))?;
+    }
+    writer.flush()
+}
+
+/// Turns the bytes of one line from a client into text, dropping telnet negotiation
+/// and applying any backspaces the client sent instead of editing the line itself.
+pub fn clean_line(raw: &[u8]) -> String {
+    let mut line: Vec<u8> = vec![];
+    let mut bytes = raw.iter().copied();
+    while let Some(byte) = bytes.next() {
+        match byte {
+            IAC => match bytes.next() {
+                // an escaped 255 is data
+                Some(IAC) => line.push(IAC),
+                Some(SB) => while !matches!(bytes.next(), Some(SE) | None) {},
+                Some(WILL..=DONT) => {
+                    bytes.next();
+                }
+                _ => {}
+            },
+            BACKSPACE | DELETE => {
+                line.pop();
+            }
+            b'\r' | b'\n' => {}
+            _ => line.push(byte),
+        }
+    }
+    String::from_utf8_lossy(&line).trim().to_owned()
+}
@@ -0,0 +1,285 @@
+use crate::actions::valid_actions;
+use crate::character::Character;
+use crate::dialogue::NodeID;
+use crate::entity::Object;
+use crate::game::{Ending, Game};
+use crate::history::History;
+use crate::inventory::Inventory;
+use crate::room::RoomID;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::time::Duration;
+
+/// Breadth-first search over every state of a world reachable through valid actions
+pub struct Solver {
+    /// makes the world to search, from its start
+    pub new_game: Box<dyn Fn() -> Game>,
+    /// extra WAIT actions tried in every state, in game minutes. The solver
+    /// always tries waiting until the world next changes by itself.
+    pub wait_steps: Vec<u64>,
+    /// the longest the solver will wait for something to happen, in game minutes
+    pub max_wait: u64,
+    /// states past this game time (in seconds since the start) are not explored
+    pub time_limit: Option<u64>,
+    /// stop exploring after this many distinct states
+    pub max_states: usize,
+}
+
+struct Node {
+    parent: Option<usize>,
+    action: String,
+    elapsed: u64,
+    ending: Option<Ending>,
+    children: Vec<usize>,
+    /// past the time limit, so never explored
+    cut_off: bool,
+}
+
+pub struct Report {
+    /// number of distinct states explored
+    pub states: usize,
+    /// false if the search gave up after `max_states`
+    pub complete: bool,
+    pub shortest_win: Option<Vec<String>>,
+    /// the earliest game time (in seconds since the start) at which the game can be won
+    pub earliest_win: Option<u64>,
+    /// number of states from which the game can no longer be won
+    pub dead_ends: usize,
+    /// shortest paths to the first states from which the game can no longer be won
+    pub points_of_no_return: Vec<Vec<String>>,
+}
+
+impl Solver {
+    pub fn new<F: Fn() -> Game + 'static>(new_game: F) -> Self {
+        Solver {
+            new_game: Box::new(new_game),
+            wait_steps: vec![],
+            max_wait: 24 * 60,
+            time_limit: None,
+            max_states: 1_000_000,
+        }
+    }
+
+    pub fn solve(&self) -> Report {
+        let mut game = (self.new_game)();
+        game.history = History::disabled();
+
+        let mut nodes = vec![Node {
+            parent: None,
+            action: String::new(),
+            elapsed: game.state.clock.elapsed_secs(),
+            ending: None,
+            children: vec![],
+            cut_off: false,
+        }];
+        let mut seen = HashMap::new();
+        seen.insert(state_hash(&game), 0);
+        let mut queue = VecDeque::new();
+        queue.push_back((0, game));
+
+        let mut complete = true;
+        while let Some((index, game)) = queue.pop_front() {
+            let mut actions = valid_actions(&game);
+            for minutes in &self.wait_steps {
+                actions.push(wait_action(*minutes));
+            }
+            if let Some(minutes) = self.minutes_until_change(&game) {
+                actions.push(wait_action(minutes));
+            }
+            for action in actions {
+                let mut next = game.clone();
+                next.step(&action);
+                let hash = state_hash(&next);
+                let child = match seen.get(&hash) {
+                    Some(child) => *child,
+                    None => {
+                        if nodes.len() >= self.max_states {
+                            complete = false;
+                            continue;
+                        }
+                        let child = nodes.len();
+                        seen.insert(hash, child);
+                        nodes.push(Node {
+                            parent: Some(index),
+                            action,
+                            elapsed: next.state.clock.elapsed_secs(),
+                            ending: next.ending(),
+                            children: vec![],
+                            cut_off: false,
+                        });
+                        let elapsed = next.state.clock.elapsed_secs();
+                        let in_time = self.time_limit.iter().all(|limit| elapsed <= *limit);
+                        if next.ending().is_none() {
+                            if in_time {
+                                queue.push_back((child, next));
+                            } else {
+                                nodes[child].cut_off = true;
+                            }
+                        }
+                        child
+                    }
+                };
+                if child != index && !nodes[index].children.contains(&child) {
+                    nodes[index].children.push(child);
+                }
+            }
+        }
+
+        let winnable = winnable_states(&nodes);
+        let shortest_win = (0..nodes.len())
+            .find(|i| nodes[*i].ending == Some(Ending::Won))
+            .map(|i| path_to(&nodes, i));
+        let earliest_win = nodes
+            .iter()
+            .filter(|node| node.ending == Some(Ending::Won))
+            .map(|node| node.elapsed)
+            .min();
+        let dead_ends = winnable.iter().filter(|winnable| !**winnable).count();
+        // dead ends whose parent could still have won, in the order they were found
+        let points_of_no_return = (0..nodes.len())
+            .filter(|i| !winnable[*i] && nodes[*i].ending.is_none())
+            .filter(|i| nodes[*i].parent.iter().any(|parent| winnable[*parent]))
+            .map(|i| path_to(&nodes, i))
+            .collect();
+
+        Report {
+            states: nodes.len(),
+            complete,
+            shortest_win,
+            earliest_win,
+            dead_ends,
+            points_of_no_return,
+        }
+    }
+}
+
+impl Solver {
+    /// Waits a minute at a time, and returns how long it takes for timers or
+    /// schedules to change the world
+    fn minutes_until_change(&self, game: &Game) -> Option<u64> {
+        let hash = state_hash_ignoring_time(game);
+        let mut next = game.clone();
+        (1..=self.max_wait).find(|_| {
+            next.state.clock.advance(Duration::from_secs(1));
+            next.update_world();
+            state_hash_ignoring_time(&next) != hash
+        })
+    }
+}
+
+fn wait_action(minutes: u64) -> String {
+    if minutes == 1 {
+        "wait 1 minute".to_owned()
+    } else {
+        format!("wait {} minutes", minutes)
+    }
+}
+
+fn state_hash(game: &Game) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    game.state.clock.elapsed().hash(&mut hasher);
+    state_hash_ignoring_time(game).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Hashes the parts of the world that can change during play. Anything
+/// whose order doesn't matter is sorted, so that taking the watch and then
+/// the mug ends up in the same state as taking the mug and then the watch.
+fn state_hash_ignoring_time(game: &Game) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    let state = &game.state;
+    game.player.at.hash(&mut hasher);
+    hash_inventory(&game.player.inventory, &mut hasher);
+    // nothing tells a second visit from a third, so the count stops at two
+    for i in 0..game.rooms.len() {
+        game.player.visits(RoomID(i)).min(2).hash(&mut hasher);
+    }
+    for room in &game.rooms {
+        for door in &room.doors {
+            door.is_open.hash(&mut hasher);
+        }
+        hash_inventory(&room.inventory, &mut hasher);
+        let mut characters: Vec<&Character> = room.characters.iter().collect();
+        characters.sort_by_key(|character| character.name);
+        for character in characters {
+            character.name.hash(&mut hasher);
+            hash_inventory(&character.inventory, &mut hasher);
+            // only nodes that can't be repeated change what happens next
+            let dialogue = &character.dialogue;
+            let once: Vec<NodeID> = dialogue
+                .visited
+                .iter()
+                .copied()
+                .filter(|id| dialogue.node(*id).once)
+                .collect();
+            sorted(&once).hash(&mut hasher);
+            if state.talking_to == Some(character.name) {
+                dialogue.current.hash(&mut hasher);
+            }
+            character.is_following.hash(&mut hasher);
+            character.schedule.next.hash(&mut hasher);
+        }
+    }
+    state.vars.hash(&mut hasher);
+    state.talking_to.hash(&mut hasher);
+    sorted(&state.timers_fired).hash(&mut hasher);
+    let scenes: Vec<&str> = state
+        .scene_triggers
+        .iter()
+        .map(|(_, scene)| *scene)
+        .collect();
+    sorted(&scenes).hash(&mut hasher);
+    hasher.finish()
+}
+
+fn hash_inventory(inventory: &Inventory, hasher: &mut DefaultHasher) {
+    let mut objects: Vec<&Object> = inventory.objects.iter().collect();
+    objects.sort_by_key(|object| object.name);
+    for object in objects {
+        object.name.hash(hasher);
+        object.can_take.hash(hasher);
+        sorted(&object.inventory).hash(hasher);
+    }
+}
+
+fn sorted<T: Ord + Clone>(items: &[T]) -> Vec<T> {
+    let mut items = items.to_vec();
+    items.sort();
+    items
+}
+
+fn path_to(nodes: &[Node], mut index: usize) -> Vec<String> {
+    let mut path = vec![];
+    while let Some(parent) = nodes[index].parent {
+        path.push(nodes[index].action.clone());
+        index = parent;
+    }
+    path.reverse();
+    path
+}
+
+/// Marks every state from which a winning ending can still be reached.
+/// States that were cut off are given the benefit of the doubt.
+fn winnable_states(nodes: &[Node]) -> Vec<bool> {
+    let mut parents = vec![vec![]; nodes.len()];
+    for (i, node) in nodes.iter().enumerate() {
+        for child in &node.children {
+            parents[*child].push(i);
+        }
+    }
+    let mut winnable: Vec<bool> = nodes
+        .iter()
+        .map(|node| node.ending == Some(Ending::Won) || node.cut_off)
+        .collect();
+    let mut queue: VecDeque<usize> = (0..nodes.len()).filter(|i| winnable[*i]).collect();
+    while let Some(i) = queue.pop_front() {
+        for parent in &parents[i] {
+            if !winnable[*parent] {
+                winnable[*parent] = true;
+                queue.push_back(*parent);
+            }
+        }
+    }
+    winnable
+}
@@ -0,0 +1,137 @@
+use crate::game::{Ending, Game};
+use std::fs;
+use std::path::Path;
+
+/// One line of input and everything the game printed in response
+#[derive(Debug, Clone, PartialEq)]
+pub struct Turn {
+    pub input: String,
+    pub output: String,
+}
+
+/// A played-through game, in the same shape as a golden file:
+///
+/// ```text
+/// (room description printed when the game starts)
+///
+/// > take watch
+/// You take the watch.
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transcript {
+    pub opening: String,
+    pub turns: Vec<Turn>,
+}
+
+impl Transcript {
+    pub fn parse(text: &str) -> Self {
+        let mut opening = vec![];
+        let mut turns: Vec<(String, Vec<&str>)> = vec![];
+        for line in text.lines() {
+            if let Some(input) = line.strip_prefix("> ") {
+                turns.push((input.trim().to_owned(), vec![]));
+            } else if let Some((_, output)) = turns.last_mut() {
+                output.push(line);
+            } else {
+                opening.push(line);
+            }
+        }
+        Transcript {
+            opening: opening.join("\n").trim_end().to_owned(),
+            turns: turns
+                .into_iter()
+                .map(|(input, output)| Turn {
+                    input,
+                    output: output.join("\n").trim_end().to_owned(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Plays the inputs in order, stopping early if the game ends
+    pub fn record<'a, I>(game: &mut Game, inputs: I) -> Self
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let mut transcript = Transcript {
+            opening: game.describe_room(),
+            turns: vec![],
+        };
+        for input in inputs {
+            if game.ending().is_some() {
+                break;
+            }
+            let mut output = game.step(input);
+            match game.ending() {
+                Some(Ending::Won) => output.push("[game over: won]".to_owned()),
+                Some(Ending::Lost) => output.push("[game over: lost]".to_owned()),
+                None => {}
+            }
+            transcript.turns.push(Turn {
+                input: input.to_owned(),
+                output: output.join("\n"),
+            });
+        }
+        transcript
+    }
+
+    pub fn inputs(&self) -> impl Iterator<Item = &str> {
+        self.turns.iter().map(|turn| turn.input.as_str())
+    }
+
+    pub fn render(&self) -> String {
+        let mut text = format!("{}\n", self.opening);
+        for turn in &self.turns {
+            text += &format!("\n> {}\n", turn.input);
+            if !turn.output.is_empty() {
+                text += &format!("{}\n", turn.output);
+            }
+        }
+        text
+    }
+
+    /// Describes the first place where `actual` differs from this transcript
+    pub fn diff(&self, actual: &Transcript) -> Option<String> {
+        if self.opening != actual.opening {
+            return Some(format!(
+                "opening differs\n--- expected\n{}\n--- actual\n{}",
+                self.opening, actual.opening
+            ));
+        }
+        for (i, (expected, actual)) in self.turns.iter().zip(&actual.turns).enumerate() {
+            if expected != actual {
+                return Some(format!(
+                    "turn {} (> {}) differs\n--- expected\n{}\n--- actual\n{}",
+                    i + 1,
+                    expected.input,
+                    expected.output,
+                    actual.output
+                ));
+            }
+        }
+        if self.turns.len() != actual.turns.len() {
+            return Some(format!(
+                "expected {} turns, but the game played {}",
+                self.turns.len(),
+                actual.turns.len()
+            ));
+        }
+        None
+    }
+}
+
+/// Replays the inputs of a golden file against a fresh game and compares the output.
+/// With `bless`, the file is rewritten with whatever the game printed instead.
+pub fn check_file(path: &Path, new_game: fn() -> Game, bless: bool) -> Result<(), String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let expected = Transcript::parse(&text);
+    let actual = Transcript::record(&mut new_game(), expected.inputs());
+    if bless {
+        fs::write(path, actual.render()).map_err(|e| format!("{}: {}", path.display(), e))
+    } else {
+        match expected.diff(&actual) {
+            Some(diff) => Err(format!("{}: {}", path.display(), diff)),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,247 @@
+use crate::game::Game;
+use crate::player::Player;
+use crate::util::watch_time;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal;
+use crossterm::{cursor, execute, queue};
+use std::io::{self, Write};
+use std::time::Duration;
+
+const SIDE_PANEL_WIDTH: usize = 26;
+/// how often the status bar is redrawn while the player is thinking
+const TICK: Duration = Duration::from_millis(250);
+
+/// A full-screen frontend: the story scrolls on the left, the inventory sits on the
+/// right, and a status bar keeps an eye on the time.
+pub struct Tui {
+    story: Vec<String>,
+    input: String,
+    history: Vec<String>,
+    /// which history entry is being edited, if the player has gone back through them
+    history_pos: Option<usize>,
+    /// how many lines the story has been scrolled back
+    scroll: usize,
+    /// game time at which the world ends, shown as time remaining
+    pub deadline: Option<u64>,
+}
+
+impl Tui {
+    pub fn new(deadline: Option<u64>) -> Self {
+        Tui {
+            story: vec![],
+            input: String::new(),
+            history: vec![],
+            history_pos: None,
+            scroll: 0,
+            deadline,
+        }
+    }
+
+    /// Plays until the game ends or the player presses Esc, then gives the terminal back.
+    /// When the game ends, the last of the story stays up until a key is pressed.
+    /// `play` turns each line the player enters into the replies to show.
+    pub fn run<F>(&mut self, game: &mut Game, play: F) -> io::Result<()>
+    where
+        F: FnMut(&mut Game, &str) -> Vec<String>,
+    {
+        let mut stdout = io::stdout();
+        terminal::enable_raw_mode()?;
+        execute!(stdout, terminal::EnterAlternateScreen, cursor::Hide)?;
+        let result = self.play(game, play, &mut stdout);
+        execute!(stdout, cursor::Show, terminal::LeaveAlternateScreen)?;
+        terminal::disable_raw_mode()?;
+        result
+    }
+
+    fn play<F, W>(&mut self, game: &mut Game, mut play: F, out: &mut W) -> io::Result<()>
+    where
+        F: FnMut(&mut Game, &str) -> Vec<String>,
+        W: Write,
+    {
+        self.story.push(game.describe_room());
+        while game.ending().is_none() {
+            self.draw(game, out)?;
+            if !event::poll(TICK)? {
+                continue;
+            }
+            let key = match event::read()? {
+                Event::Key(key) if key.kind != KeyEventKind::Release => key,
+                _ => continue,
+            };
+            if is_quit(&key) {
+                break;
+            }
+            if let Some(line) = self.handle_key(key) {
+                self.story.push(format!("> {}", line));
+                self.story.extend(play(game, &line));
+            }
+        }
+        if game.ending().is_some() {
+            self.story.push("[Press any key.]".to_owned());
+            self.draw(game, out)?;
+            loop {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind != KeyEventKind::Release {
+                        break;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Edits the input line. Returns a whole line once the player presses Enter.
+    fn handle_key(&mut self, key: KeyEvent) -> Option<String> {
+        match key.code {
+            KeyCode::Enter => {
+                let line = self.input.trim().to_owned();
+                self.input.clear();
+                self.history_pos = None;
+                self.scroll = 0;
+                if line.is_empty() {
+                    return None;
+                }
+                if self.history.last() != Some(&line) {
+                    self.history.push(line.clone());
+                }
+                return Some(line);
+            }
+            KeyCode::Char(c) => self.input.push(c),
+            KeyCode::Backspace => {
+                self.input.pop();
+            }
+            KeyCode::Up if !self.history.is_empty() => {
+                let pos = match self.history_pos {
+                    Some(pos) => pos.saturating_sub(1),
+                    None => self.history.len() - 1,
+                };
+                self.history_pos = Some(pos);
+                self.input = self.history[pos].clone();
+            }
+            KeyCode::Down => match self.history_pos {
+                Some(pos) if pos + 1 < self.history.len() => {
+                    self.history_pos = Some(pos + 1);
+                    self.input = self.history[pos + 1].clone();
+                }
+                Some(_) => {
+                    self.history_pos = None;
+                    self.input.clear();
+                }
+                None => {}
+            },
+            KeyCode::PageUp => self.scroll += 5,
+            KeyCode::PageDown => self.scroll = self.scroll.saturating_sub(5),
+            _ => {}
+        }
+        None
+    }
+
+    fn draw<W: Write>(&self, game: &Game, out: &mut W) -> io::Result<()> {
+        let (width, height) = terminal::size()?;
+        let (width, height) = (width as usize, height as usize);
+        let story_width = width.saturating_sub(SIDE_PANEL_WIDTH + 1).max(1);
+        let pane_height = height.saturating_sub(2);
+
+        let story: Vec<String> = self
+            .story
+            .iter()
+            .flat_map(|text| {
+                let mut lines = wrap(text, story_width);
+                lines.push(String::new());
+                lines
+            })
+            .collect();
+        let bottom = story.len().saturating_sub(self.scroll.min(story.len()));
+        let top = bottom.saturating_sub(pane_height);
+        let side = inventory_lines(&game.player);
+
+        // every row is padded to full width, so nothing needs clearing first
+        queue!(out, cursor::MoveTo(0, 0))?;
+        queue!(
+            out,
+            SetAttribute(Attribute::Reverse),
+            Print(pad(&status_line(game, self.deadline), width)),
+            SetAttribute(Attribute::Reset)
+        )?;
+        for row in 0..pane_height {
+            let text = story.get(top + row).map(String::as_str).unwrap_or("");
+            let panel = side.get(row).map(String::as_str).unwrap_or("");
+            queue!(
+                out,
+                cursor::MoveTo(0, (row + 1) as u16),
+                Print(pad(text, story_width)),
+                Print("│"),
+                Print(pad(panel, SIDE_PANEL_WIDTH))
+            )?;
+        }
+        queue!(
+            out,
+            cursor::MoveTo(0, height.saturating_sub(1) as u16),
+            Print(pad(&format!("> {}", self.input), width))
+        )?;
+        out.flush()
+    }
+}
+
+fn is_quit(key: &KeyEvent) -> bool {
+    key.code == KeyCode::Esc
+        || (key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL))
+}
+
+/// The room, the time on the watch, and how long is left
+pub fn status_line(game: &Game, deadline: Option<u64>) -> String {
+    let here = game.player.get_curr_room(&game.rooms);
+    let elapsed = game.state.clock.elapsed();
+    let mut status = format!(" {} | {}", here.name, watch_time(elapsed));
+    if let Some(deadline) = deadline {
+        let left = deadline.saturating_sub(elapsed.as_secs());
+        status += &format!(" | {}:{:02} left", left / 60, left % 60);
+    }
+    status
+}
+
+/// The player's inventory, with whatever each object holds indented beneath it
+pub fn inventory_lines(player: &Player) -> Vec<String> {
+    let mut lines = vec![" Inventory".to_owned()];
+    if player.inventory.objects.is_empty() {
+        lines.push("   (empty)".to_owned());
+    }
+    for object in &player.inventory.objects {
+        lines.push(format!(" - {}", object.name));
+        for content in &object.inventory {
+            lines.push(format!("     {}", content));
+        }
+    }
+    lines
+}
+
+/// Breaks text into lines no wider than `width`, at spaces where possible
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = vec![];
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split(' ') {
+            if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+                lines.push(line);
+                line = String::new();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line += word;
+            while line.chars().count() > width {
+                let rest = line.split_off(line.char_indices().nth(width).unwrap().0);
+                lines.push(line);
+                line = rest;
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+fn pad(text: &str, width: usize) -> String {
+    let text: String = text.chars().take(width).collect();
+    format!("{:width$}", text, width = width)
+}
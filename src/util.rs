@@ -1,4 +1,7 @@
+use std::collections::BTreeSet;
 use std::io;
+use std::sync::Mutex;
+use std::time::Duration;
 
 pub enum Direction {
     North,
@@ -7,12 +10,55 @@ pub enum Direction {
     West,
 }
 
-pub fn get_trimmed_input(input: &mut String) -> &str {
+// text made up while playing, by scripts or debug commands, lives as long as the
+// world's text does, but each piece is only kept once however often it's asked for
+static INTERNED: Mutex<BTreeSet<&'static str>> = Mutex::new(BTreeSet::new());
+
+pub fn intern(text: &str) -> &'static str {
+    let mut interned = INTERNED.lock().unwrap();
+    match interned.get(text) {
+        Some(text) => text,
+        None => {
+            let text: &'static str = Box::leak(text.to_owned().into_boxed_str());
+            interned.insert(text);
+            text
+        }
+    }
+}
+
+/// The next line from stdin, or `None` once there's no more input
+pub fn get_trimmed_input(input: &mut String) -> Option<&str> {
     input.clear();
-    io::stdin().read_line(input).unwrap();
-    input.trim()
+    match io::stdin().read_line(input).unwrap() {
+        0 => None,
+        _ => Some(input.trim()),
+    }
+}
+
+/// "a", "a and b", "a, b and c"
+pub fn english_list(items: &[String]) -> String {
+    match items {
+        [] => String::new(),
+        [item] => item.clone(),
+        [rest @ .., last] => format!("{} and {}", rest.join(", "), last),
+    }
+}
+
+pub fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
 }
 
 pub fn get_room_name_border() -> &'static str {
     "==============="
 }
+
+/// Game time as it reads on the player's watch, which starts at 7:00pm
+pub fn watch_time(elapsed: Duration) -> String {
+    let hour = 7 + (elapsed.as_secs() / 60);
+    let minute = elapsed.as_secs() % 60;
+    format!("{}:{:02}pm", hour, minute)
+}
@@ -0,0 +1,104 @@
+//! The story's own state: named values a world keeps track of, like how many coins
+//! the player has or when the tea started brewing. Flags are variables holding `true`,
+//! and the `won` and `lost` flags end the story. Writers can list them all with `@vars`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Value {
+    Bool(bool),
+    Int(i64),
+    Str(String),
+    /// game time, in seconds
+    Time(u64),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Bool(value) => write!(f, "{}", value),
+            Value::Int(value) => write!(f, "{}", value),
+            Value::Str(value) => write!(f, "{:?}", value),
+            Value::Time(secs) => write!(f, "time {}", secs),
+        }
+    }
+}
+
+/// Variables by name, kept in order so they list and save the same way every time
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[serde(transparent)]
+pub struct Vars {
+    values: BTreeMap<String, Value>,
+}
+
+impl Vars {
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.values.get(name)
+    }
+
+    pub fn set(&mut self, name: &str, value: Value) {
+        self.values.insert(name.to_owned(), value);
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Value> {
+        self.values.remove(name)
+    }
+
+    /// Whether the variable holds `true`. Unset variables are false.
+    pub fn bool(&self, name: &str) -> bool {
+        self.get(name) == Some(&Value::Bool(true))
+    }
+
+    /// Unset variables count as 0
+    pub fn int(&self, name: &str) -> i64 {
+        match self.get(name) {
+            Some(Value::Int(value)) => *value,
+            _ => 0,
+        }
+    }
+
+    pub fn string(&self, name: &str) -> Option<&str> {
+        match self.get(name) {
+            Some(Value::Str(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn time(&self, name: &str) -> Option<u64> {
+        match self.get(name) {
+            Some(Value::Time(secs)) => Some(*secs),
+            _ => None,
+        }
+    }
+
+    /// Adds to an integer variable, starting from 0 if it isn't one
+    pub fn add(&mut self, name: &str, by: i64) {
+        let value = self.int(name).saturating_add(by);
+        self.set(name, Value::Int(value));
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.values
+            .iter()
+            .map(|(name, value)| (name.as_str(), value))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// One `name = value` line per variable, for debugging
+    pub fn describe(&self) -> String {
+        if self.is_empty() {
+            return "No variables are set.".to_owned();
+        }
+        let lines: Vec<String> = self
+            .iter()
+            .map(|(name, value)| format!("{} = {}", name, value))
+            .collect();
+        lines.join("\n")
+    }
+}
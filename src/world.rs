@@ -0,0 +1,399 @@
+//! Stable IDs for everything in a world, and indexes over them. Objects,
+//! characters and doors carry their ID with them as they move about, so an ID
+//! keeps pointing at the same thing after it is picked up, handed over or renamed
+//! (the mug that becomes "brewed tea" is still the same object).
+//!
+//! The indexes answer "where is X" and "what is called Y here" without searching
+//! every room, and the parser and command handlers find things through them. To keep
+//! them right, objects and characters are moved, made, destroyed and renamed through
+//! `World`. Anything that changes the world some other way should call `Game::reindex`.
+
+use crate::character::Character;
+use crate::door::Door;
+use crate::entity::Object;
+use crate::inventory::Inventory;
+use crate::player::Player;
+use crate::room::{Room, RoomID};
+use std::collections::HashMap;
+
+// IDs are handed out from 1, so the default of 0 means "not registered yet"
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Hash, Default)]
+pub struct ObjectID(pub usize);
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Hash, Default)]
+pub struct CharacterID(pub usize);
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Hash, Default)]
+pub struct DoorID(pub usize);
+
+/// Players are numbered from 0, in the order they join. A game for one has only player 0.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Hash, Default)]
+pub struct PlayerID(pub usize);
+
+/// Anything a command can be about
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
+pub enum Entity {
+    Object(ObjectID),
+    Character(CharacterID),
+    Door(DoorID),
+    Room(RoomID),
+}
+
+impl Entity {
+    pub fn object(self) -> Option<ObjectID> {
+        match self {
+            Entity::Object(id) => Some(id),
+            _ => None,
+        }
+    }
+
+    pub fn character(self) -> Option<CharacterID> {
+        match self {
+            Entity::Character(id) => Some(id),
+            _ => None,
+        }
+    }
+}
+
+/// Where an object is
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Hash)]
+pub enum Location {
+    Room(RoomID),
+    Player(PlayerID),
+    Character(CharacterID),
+}
+
+/// Everything by each name in one place, in the order it got there. The first one wins.
+type Names<ID> = HashMap<&'static str, Vec<ID>>;
+
+#[derive(Debug, Clone, Default)]
+pub struct World {
+    last_id: usize,
+    objects: HashMap<ObjectID, Location>,
+    characters: HashMap<CharacterID, RoomID>,
+    doors: HashMap<DoorID, RoomID>,
+    /// room names, in lower case
+    rooms: HashMap<String, RoomID>,
+    // names, one index per scope
+    object_names: HashMap<Location, Names<ObjectID>>,
+    character_names: HashMap<RoomID, Names<CharacterID>>,
+    door_directions: HashMap<RoomID, HashMap<&'static str, DoorID>>,
+}
+
+impl World {
+    /// Indexes a world, giving everything in it an ID
+    pub fn new(player: &mut Player, rooms: &mut [Room]) -> Self {
+        let mut world = World::default();
+        world.index(player, rooms);
+        world
+    }
+
+    /// Gives an ID to anything that doesn't have one yet, and rebuilds the indexes
+    /// from scratch. Only `player` is indexed, so this is for games with one player.
+    pub fn index(&mut self, player: &mut Player, rooms: &mut [Room]) {
+        *self = World {
+            last_id: self.last_id,
+            ..Default::default()
+        };
+        for (i, room) in rooms.iter_mut().enumerate() {
+            let room_id = RoomID(i);
+            self.rooms
+                .entry(room.name.to_lowercase())
+                .or_insert(room_id);
+            for door in &mut room.doors {
+                // copies of a registered thing get IDs of their own
+                if door.id == DoorID(0) || self.doors.contains_key(&door.id) {
+                    door.id = DoorID(self.next_id());
+                }
+                self.doors.insert(door.id, room_id);
+                self.door_directions
+                    .entry(room_id)
+                    .or_default()
+                    .entry(door.direction)
+                    .or_insert(door.id);
+            }
+            for object in &mut room.inventory.objects {
+                self.index_object(object, Location::Room(room_id));
+            }
+            for character in &mut room.characters {
+                self.index_character(character, room_id);
+            }
+        }
+        self.add_player(player);
+    }
+
+    /// Indexes what a player is carrying, for players who join a world already in play
+    pub fn add_player(&mut self, player: &mut Player) {
+        for object in &mut player.inventory.objects {
+            self.index_object(object, Location::Player(player.id));
+        }
+    }
+
+    /// Forgets what a player was carrying, for players who leave
+    pub fn remove_player(&mut self, player: &Player) {
+        for object in &player.inventory.objects {
+            self.forget_object(object);
+        }
+        self.object_names.remove(&Location::Player(player.id));
+    }
+
+    fn next_id(&mut self) -> usize {
+        self.last_id += 1;
+        self.last_id
+    }
+
+    fn index_object(&mut self, object: &mut Object, at: Location) {
+        if object.id == ObjectID(0) || self.objects.contains_key(&object.id) {
+            object.id = ObjectID(self.next_id());
+        }
+        self.objects.insert(object.id, at);
+        self.object_names
+            .entry(at)
+            .or_default()
+            .entry(object.name)
+            .or_default()
+            .push(object.id);
+    }
+
+    fn forget_object(&mut self, object: &Object) {
+        if let Some(at) = self.objects.remove(&object.id) {
+            forget_name(&mut self.object_names, at, object.name, object.id);
+        }
+    }
+
+    fn index_character(&mut self, character: &mut Character, room: RoomID) {
+        if character.id == CharacterID(0) || self.characters.contains_key(&character.id) {
+            character.id = CharacterID(self.next_id());
+        }
+        self.characters.insert(character.id, room);
+        self.character_names
+            .entry(room)
+            .or_default()
+            .entry(character.name)
+            .or_default()
+            .push(character.id);
+        for object in &mut character.inventory.objects {
+            self.index_object(object, Location::Character(character.id));
+        }
+    }
+
+    /// -------------------------
+    /// changing the world
+    /// -------------------------
+    /// Puts an object somewhere, giving it an ID if it's new. Returns the ID, or
+    /// nothing if `at` is another player, who can't be reached from here.
+    pub fn add_object(
+        &mut self,
+        mut object: Object,
+        at: Location,
+        player: &mut Player,
+        rooms: &mut [Room],
+    ) -> Option<ObjectID> {
+        self.inventory_mut(at, player, rooms)?;
+        self.index_object(&mut object, at);
+        let id = object.id;
+        self.inventory_mut(at, player, rooms)?.add(object);
+        Some(id)
+    }
+
+    /// Takes an object out of the world, whether or not it could be picked up
+    pub fn remove_object(
+        &mut self,
+        id: ObjectID,
+        player: &mut Player,
+        rooms: &mut [Room],
+    ) -> Option<Object> {
+        let inventory = self.inventory_mut(self.locate(id)?, player, rooms)?;
+        let i = inventory
+            .objects
+            .iter()
+            .position(|object| object.id == id)?;
+        let object = inventory.objects.remove(i);
+        self.forget_object(&object);
+        Some(object)
+    }
+
+    /// Moves an object from wherever it is to `to`, keeping its ID
+    pub fn move_object(
+        &mut self,
+        id: ObjectID,
+        to: Location,
+        player: &mut Player,
+        rooms: &mut [Room],
+    ) -> bool {
+        if self.inventory_mut(to, player, rooms).is_none() {
+            return false;
+        }
+        match self.remove_object(id, player, rooms) {
+            Some(object) => self.add_object(object, to, player, rooms).is_some(),
+            None => false,
+        }
+    }
+
+    pub fn rename_object(
+        &mut self,
+        id: ObjectID,
+        name: &'static str,
+        player: &mut Player,
+        rooms: &mut [Room],
+    ) {
+        let at = match self.locate(id) {
+            Some(at) => at,
+            None => return,
+        };
+        if let Some(object) = self.object_mut(id, player, rooms) {
+            let old = std::mem::replace(&mut object.name, name);
+            forget_name(&mut self.object_names, at, old, id);
+            self.object_names
+                .entry(at)
+                .or_default()
+                .entry(name)
+                .or_default()
+                .push(id);
+        }
+    }
+
+    /// Moves a character to another room, along with whatever they carry
+    pub fn move_character(&mut self, id: CharacterID, to: RoomID, rooms: &mut [Room]) -> bool {
+        let from = match self.locate_character(id) {
+            Some(from) => from,
+            None => return false,
+        };
+        let i = match rooms[from.0]
+            .characters
+            .iter()
+            .position(|character| character.id == id)
+        {
+            Some(i) => i,
+            None => return false,
+        };
+        let character = rooms[from.0].characters.remove(i);
+        forget_name(&mut self.character_names, from, character.name, id);
+        self.characters.insert(id, to);
+        self.character_names
+            .entry(to)
+            .or_default()
+            .entry(character.name)
+            .or_default()
+            .push(id);
+        rooms[to.0].characters.push(character);
+        true
+    }
+
+    fn inventory_mut<'a>(
+        &self,
+        at: Location,
+        player: &'a mut Player,
+        rooms: &'a mut [Room],
+    ) -> Option<&'a mut Inventory> {
+        match at {
+            Location::Player(id) if id == player.id => Some(&mut player.inventory),
+            Location::Player(_) => None,
+            Location::Room(room) => Some(&mut rooms.get_mut(room.0)?.inventory),
+            Location::Character(character) => {
+                Some(&mut self.character_mut(character, rooms)?.inventory)
+            }
+        }
+    }
+
+    /// -------------------------
+    /// where things are
+    /// -------------------------
+    pub fn locate(&self, id: ObjectID) -> Option<Location> {
+        self.objects.get(&id).copied()
+    }
+
+    pub fn locate_character(&self, id: CharacterID) -> Option<RoomID> {
+        self.characters.get(&id).copied()
+    }
+
+    /// The room a door leads out of
+    pub fn locate_door(&self, id: DoorID) -> Option<RoomID> {
+        self.doors.get(&id).copied()
+    }
+
+    /// -------------------------
+    /// what things are called
+    /// -------------------------
+    /// Finds a room by name, ignoring case
+    pub fn room_named(&self, name: &str) -> Option<RoomID> {
+        self.rooms.get(&name.trim().to_lowercase()).copied()
+    }
+
+    pub fn object_named(&self, at: Location, name: &str) -> Option<ObjectID> {
+        self.object_names.get(&at)?.get(name)?.first().copied()
+    }
+
+    pub fn character_named(&self, room: RoomID, name: &str) -> Option<CharacterID> {
+        self.character_names.get(&room)?.get(name)?.first().copied()
+    }
+
+    pub fn door_towards(&self, room: RoomID, direction: &str) -> Option<DoorID> {
+        self.door_directions.get(&room)?.get(direction).copied()
+    }
+
+    /// -------------------------
+    /// from IDs back to things
+    /// -------------------------
+    /// Objects carried by a player other than `player` can't be reached
+    pub fn object<'a>(
+        &self,
+        id: ObjectID,
+        player: &'a Player,
+        rooms: &'a [Room],
+    ) -> Option<&'a Object> {
+        let inventory = match self.locate(id)? {
+            Location::Player(other) if other == player.id => &player.inventory,
+            Location::Player(_) => return None,
+            Location::Room(room) => &rooms[room.0].inventory,
+            Location::Character(character) => &self.character(character, rooms)?.inventory,
+        };
+        inventory.object(id)
+    }
+
+    pub fn object_mut<'a>(
+        &self,
+        id: ObjectID,
+        player: &'a mut Player,
+        rooms: &'a mut [Room],
+    ) -> Option<&'a mut Object> {
+        self.inventory_mut(self.locate(id)?, player, rooms)?
+            .object_mut(id)
+    }
+
+    pub fn character<'a>(&self, id: CharacterID, rooms: &'a [Room]) -> Option<&'a Character> {
+        rooms[self.locate_character(id)?.0].character(id)
+    }
+
+    pub fn character_mut<'a>(
+        &self,
+        id: CharacterID,
+        rooms: &'a mut [Room],
+    ) -> Option<&'a mut Character> {
+        rooms[self.locate_character(id)?.0].character_mut(id)
+    }
+
+    pub fn door<'a>(&self, id: DoorID, rooms: &'a [Room]) -> Option<&'a Door> {
+        rooms[self.locate_door(id)?.0].door(id)
+    }
+
+    pub fn door_mut<'a>(&self, id: DoorID, rooms: &'a mut [Room]) -> Option<&'a mut Door> {
+        rooms[self.locate_door(id)?.0].door_mut(id)
+    }
+}
+
+fn forget_name<K, ID>(names: &mut HashMap<K, Names<ID>>, scope: K, name: &'static str, id: ID)
+where
+    K: Eq + std::hash::Hash,
+    ID: PartialEq,
+{
+    if let Some(names) = names.get_mut(&scope) {
+        if let Some(ids) = names.get_mut(name) {
+            ids.retain(|other| *other != id);
+            if ids.is_empty() {
+                names.remove(name);
+            }
+        }
+    }
+}
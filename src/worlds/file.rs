@@ -0,0 +1,678 @@
+//! Worlds written as JSON instead of Rust, so writers can iterate without recompiling.
+//!
+//! ```json
+//! {
+//!   "start": "Cellar",
+//!   "time_limit": 60,
+//!   "goal": { "player_has": "lantern" },
+//!   "vars": { "matches": { "int": 3 } },
+//!   "rooms": [{
+//!     "name": "Cellar",
+//!     "desc": "Damp stone walls. Stairs lead up to the north.",
+//!     "doors": [{ "direction": "north", "to": "Kitchen", "opens_at": 10 }],
+//!     "objects": [{ "name": "crate", "desc": "A crate.", "contents": [{ "name": "lantern", "desc": "Rusty." }] }],
+//!     "scripts": { "enter": "if time() > 20 { say(\"Water drips from the ceiling.\"); }" }
+//!   }],
+//!   "undo": { "depth": 10, "after_win": false, "after_loss": true },
+//!   "timers": [{ "at": 25, "script": "say(\"A bell tolls upstairs.\");" }],
+//!   "intro": "You wake up in the dark.\n[pause 2]",
+//!   "scenes": { "creak": "[sound creak.mp3]\nSomething creaks upstairs." },
+//!   "triggers": [{ "when": { "flag": "crate opened" }, "scene": "creak" }],
+//!   "rules": [{ "name": "the crate is nailed shut", "phase": "instead", "verb": "open", "object": "crate",
+//!               "when": { "player_lacks": "crowbar" }, "text": "The lid won't budge." }]
+//! }
+//! ```
+//!
+//! Scenes are written in the markup described in `cutscene`, scripts in Rhai with the
+//! hooks and functions described in `script`, and rules as described in `rules`.
+//!
+//! Text from the file is leaked into `&'static str`s, the same as the built-in worlds use.
+//! That memory is never given back, so every call to `load` keeps its world's text
+//! around until the program exits.
+
+use crate::character::Character;
+use crate::clock::Clock;
+use crate::command_handler::find_verb;
+use crate::condition::Condition;
+use crate::cutscene::{Cutscene, Cutscenes};
+use crate::description::Variant;
+use crate::dialogue::{Dialogue, DialogueNode, Effect, NodeID};
+use crate::door::Door;
+use crate::entity::Object;
+use crate::game::Game;
+use crate::game_state::{GameState, LOST_FLAG, WON_FLAG};
+use crate::history::History;
+use crate::inventory::Inventory;
+use crate::parser::Parser;
+use crate::player::Player;
+use crate::room::{Room, RoomID};
+use crate::rules::{Phase, Rule, Rulebook};
+use crate::schedule::{update_schedules, Schedule};
+use crate::script::{Hook, Script, Scripts};
+use crate::util::intern;
+use crate::vars::Value;
+use crate::world::{CharacterID, PlayerID, World};
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::mem;
+use std::path::Path;
+
+const UNDO_DEPTH: usize = 20;
+
+// played when a world doesn't write its own endings
+const WON: &str = "
+You have won.
+
+============================
+THE END
+============================";
+const LOST: &str = "
+You have lost.
+
+============================
+THE END
+============================";
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WorldDef {
+    /// name of the room the player starts in; the first room if left out
+    start: Option<String>,
+    /// game time at which the player loses
+    time_limit: Option<u64>,
+    /// the player wins as soon as this holds
+    goal: Option<ConditionDef>,
+    /// variables and their values at the start, like `{ "coins": { "int": 3 } }`
+    #[serde(default)]
+    vars: BTreeMap<String, Value>,
+    #[serde(default)]
+    inventory: Vec<ObjectDef>,
+    rooms: Vec<RoomDef>,
+    intro: Option<String>,
+    won: Option<String>,
+    lost: Option<String>,
+    /// scenes the world can cue by name
+    #[serde(default)]
+    scenes: HashMap<String, String>,
+    #[serde(default)]
+    triggers: Vec<TriggerDef>,
+    #[serde(default)]
+    timers: Vec<TimerDef>,
+    #[serde(default)]
+    rules: Vec<RuleDef>,
+    #[serde(default)]
+    undo: UndoDef,
+}
+
+/// How far back the player can undo, and whether they can undo their way out of an ending
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields, default)]
+struct UndoDef {
+    depth: usize,
+    after_win: bool,
+    after_loss: bool,
+}
+
+impl Default for UndoDef {
+    fn default() -> Self {
+        let history = History::new(UNDO_DEPTH);
+        UndoDef {
+            depth: history.depth,
+            after_win: history.after_win,
+            after_loss: history.after_loss,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleDef {
+    /// `rules[<n>]` if left out
+    name: Option<String>,
+    phase: PhaseDef,
+    verb: Option<String>,
+    object: Option<String>,
+    room: Option<String>,
+    when: Option<ConditionDef>,
+    text: Option<String>,
+    #[serde(default)]
+    effects: Vec<EffectDef>,
+    #[serde(default)]
+    veto: bool,
+    redirect: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum PhaseDef {
+    Before,
+    Instead,
+    After,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "snake_case")]
+enum EffectDef {
+    GiveItem(String),
+    TakeItem(String),
+    SetFlag(String),
+    ClearFlag(String),
+    /// `{ "open_door": ["Cellar", "north"] }`
+    OpenDoor(String, String),
+    /// `{ "set": ["coins", { "int": 3 }] }`
+    Set(String, Value),
+    Add(String, i64),
+    Follow,
+    Stay,
+}
+
+/// A script that runs once, when the game time reaches `at`
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TimerDef {
+    at: u64,
+    script: String,
+}
+
+/// Plays a scene the first time a condition holds
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TriggerDef {
+    when: ConditionDef,
+    scene: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RoomDef {
+    name: String,
+    desc: String,
+    #[serde(default)]
+    variants: Vec<VariantDef>,
+    #[serde(default)]
+    doors: Vec<DoorDef>,
+    #[serde(default)]
+    objects: Vec<ObjectDef>,
+    #[serde(default)]
+    characters: Vec<CharacterDef>,
+    ambience: Option<String>,
+    /// `enter` and `leave`
+    #[serde(default)]
+    scripts: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DoorDef {
+    direction: String,
+    to: String,
+    /// locked doors open by themselves at `opens_at`, if given
+    #[serde(default = "yes")]
+    open: bool,
+    opens_at: Option<u64>,
+    /// the door only lets the player through while this holds
+    guard: Option<ConditionDef>,
+    msg_on_open: Option<String>,
+    msg_on_closed: Option<String>,
+    sound_on_open: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ObjectDef {
+    name: String,
+    desc: String,
+    #[serde(default)]
+    variants: Vec<VariantDef>,
+    /// objects inside this one, which can be taken once it has been examined
+    #[serde(default)]
+    contents: Vec<ObjectDef>,
+    #[serde(default)]
+    accepts: Vec<String>,
+    #[serde(default)]
+    can_take: bool,
+    /// mentioned in the room's description already, so not listed again
+    #[serde(default)]
+    scenery: bool,
+    /// `before <verb>` and `after <verb>`
+    #[serde(default)]
+    scripts: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CharacterDef {
+    name: String,
+    desc: String,
+    #[serde(default)]
+    variants: Vec<VariantDef>,
+    /// what the character says when talked to
+    greeting: Option<String>,
+    #[serde(default)]
+    inventory: Vec<ObjectDef>,
+    #[serde(default)]
+    will_follow: bool,
+    /// `talk`, `before <verb>` and `after <verb>`
+    #[serde(default)]
+    scripts: BTreeMap<String, String>,
+}
+
+/// A description to use while a condition holds
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct VariantDef {
+    when: ConditionDef,
+    text: String,
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields, rename_all = "snake_case")]
+enum ConditionDef {
+    Flag(String),
+    NotFlag(String),
+    PlayerHas(String),
+    PlayerLacks(String),
+    FirstVisit,
+    After(u64),
+    Before(u64),
+    /// `{ "contains": ["mug", "tea"] }`
+    Contains(String, String),
+    Present(String),
+    /// `{ "is": ["mood", { "str": "grumpy" }] }`
+    Is(String, Value),
+    AtLeast(String, i64),
+    Not(Box<ConditionDef>),
+    All(Vec<ConditionDef>),
+    Any(Vec<ConditionDef>),
+}
+
+fn yes() -> bool {
+    true
+}
+
+fn leak(text: String) -> &'static str {
+    Box::leak(text.into_boxed_str())
+}
+
+/// Reads a world file. Errors name the file, and the line and column where there is one.
+pub fn load(path: &Path, clock: Clock) -> Result<Game, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let mut game = parse(&text, clock).map_err(|e| format!("{}: {}", path.display(), e))?;
+    for script in &mut game.scripts.scripts {
+        script.location = format!("{}: {}", path.display(), script.location);
+    }
+    Ok(game)
+}
+
+pub fn parse(text: &str, clock: Clock) -> Result<Game, String> {
+    let def: WorldDef = serde_json::from_str(text).map_err(|e| e.to_string())?;
+    if def.rooms.is_empty() {
+        return Err("a world needs at least one room".to_owned());
+    }
+    let scripts = Scripts::new(build_scripts(&def)?);
+    let room_id = |name: &str| {
+        def.rooms
+            .iter()
+            .position(|room| room.name == name)
+            .map(RoomID)
+            .ok_or_else(|| format!("there is no room called `{}`", name))
+    };
+
+    let mut rooms = vec![];
+    for room in &def.rooms {
+        let mut doors = vec![];
+        for door in &room.doors {
+            doors.push(Door {
+                target: room_id(&door.to)?,
+                direction: leak(door.direction.clone()),
+                is_open: door.open && door.opens_at.is_none(),
+                opens_at: door.opens_at,
+                guard: door.guard.clone().map(build_condition),
+                msg_on_open: door.msg_on_open.clone().map(leak),
+                msg_on_closed: door.msg_on_closed.clone().map(leak),
+                sound_on_open: door.sound_on_open.clone().map(leak),
+                ..Default::default()
+            });
+        }
+        rooms.push(Room {
+            name: leak(room.name.clone()),
+            desc: leak(room.desc.clone()),
+            variants: build_variants(&room.variants),
+            doors,
+            inventory: build_inventory(&room.objects),
+            characters: room.characters.iter().map(build_character).collect(),
+            ambience: room.ambience.clone().map(leak),
+        });
+    }
+    let mut rules = Rulebook::default();
+    for (i, rule) in def.rules.iter().enumerate() {
+        rules
+            .rules
+            .push(build_rule(rule, &format!("rules[{}]", i), &room_id)?);
+    }
+    let start = match &def.start {
+        Some(name) => room_id(name)?,
+        None => RoomID(0),
+    };
+
+    let scene = |markup: &Option<String>, name: &str| match markup {
+        Some(markup) => Cutscene::parse(leak(markup.clone()))
+            .map(Some)
+            .map_err(|e| format!("{}: {}", name, e)),
+        None => Ok(None),
+    };
+    let mut cutscenes = Cutscenes {
+        intro: scene(&def.intro, "intro")?,
+        won: scene(&def.won, "won")?.or_else(|| Cutscene::parse(WON).ok()),
+        lost: scene(&def.lost, "lost")?.or_else(|| Cutscene::parse(LOST).ok()),
+        ..Default::default()
+    };
+    for (name, markup) in &def.scenes {
+        let parsed = scene(&Some(markup.clone()), &format!("scene `{}`", name))?;
+        cutscenes
+            .named
+            .insert(leak(name.clone()), parsed.unwrap_or_default());
+    }
+
+    let mut state = GameState::new(clock);
+    state.time_limit = def.time_limit;
+    state.goal = def.goal.map(build_condition);
+    for (name, value) in def.vars {
+        state.vars.set(&name, value);
+    }
+    for trigger in def.triggers {
+        let (&name, _) = cutscenes
+            .named
+            .get_key_value(trigger.scene.as_str())
+            .ok_or_else(|| format!("there is no scene called `{}`", trigger.scene))?;
+        state
+            .scene_triggers
+            .push((build_condition(trigger.when), name));
+    }
+    let mut player = Player {
+        id: PlayerID(0),
+        name: "me",
+        desc: "a person",
+        at: start,
+        inventory: build_inventory(&def.inventory),
+        visits: HashMap::new(),
+        came_from: None,
+        seen: HashMap::new(),
+    };
+    let world = World::new(&mut player, &mut rooms);
+    let mut history = History::new(def.undo.depth);
+    history.after_win = def.undo.after_win;
+    history.after_loss = def.undo.after_loss;
+    Ok(Game {
+        player,
+        rooms,
+        state,
+        history,
+        update,
+        log: vec![],
+        cutscenes,
+        world,
+        scripts,
+        rules,
+        debug: false,
+    })
+}
+
+/// Compiles every script in the world. Each one remembers where in the file it was
+/// written, as a path like `rooms[0].objects[1].scripts["before take"]`.
+fn build_scripts(def: &WorldDef) -> Result<Vec<Script>, String> {
+    let mut scripts = vec![];
+    for (i, timer) in def.timers.iter().enumerate() {
+        let location = format!("timers[{}].script", i);
+        scripts.push(Script::compile(
+            Hook::Timer(timer.at),
+            &timer.script,
+            location,
+        )?);
+    }
+    for (i, room) in def.rooms.iter().enumerate() {
+        let path = format!("rooms[{}]", i);
+        for (key, source) in &room.scripts {
+            let location = format!("{}.scripts[\"{}\"]", path, key);
+            let hook = match key.as_str() {
+                "enter" => Hook::Enter(RoomID(i)),
+                "leave" => Hook::Leave(RoomID(i)),
+                _ => {
+                    return Err(format!(
+                        "{}: rooms have `enter` and `leave` scripts",
+                        location
+                    ))
+                }
+            };
+            scripts.push(Script::compile(hook, source, location)?);
+        }
+        object_scripts(&room.objects, &format!("{}.objects", path), &mut scripts)?;
+        for (j, character) in room.characters.iter().enumerate() {
+            let path = format!("{}.characters[{}]", path, j);
+            hooked_scripts(&character.name, &character.scripts, &path, &mut scripts)?;
+            object_scripts(
+                &character.inventory,
+                &format!("{}.inventory", path),
+                &mut scripts,
+            )?;
+        }
+    }
+    object_scripts(&def.inventory, "inventory", &mut scripts)?;
+    Ok(scripts)
+}
+
+fn object_scripts(defs: &[ObjectDef], path: &str, scripts: &mut Vec<Script>) -> Result<(), String> {
+    for (i, def) in defs.iter().enumerate() {
+        let path = format!("{}[{}]", path, i);
+        hooked_scripts(&def.name, &def.scripts, &path, scripts)?;
+        object_scripts(&def.contents, &format!("{}.contents", path), scripts)?;
+    }
+    Ok(())
+}
+
+/// Scripts on an object or character: `before <verb>`, `after <verb>`, and `talk`
+fn hooked_scripts(
+    name: &str,
+    defs: &BTreeMap<String, String>,
+    path: &str,
+    scripts: &mut Vec<Script>,
+) -> Result<(), String> {
+    let name = intern(name);
+    for (key, source) in defs {
+        let location = format!("{}.scripts[\"{}\"]", path, key);
+        let verb = |verb: &str| {
+            find_verb(verb)
+                .filter(|known| known.takes_one_turn())
+                .map(|known| known.name)
+                .ok_or_else(|| format!("{}: there is no verb `{}`", location, verb))
+        };
+        let hook = match key.split_once(' ') {
+            Some(("before", name_of_verb)) => Hook::Before(verb(name_of_verb)?, name),
+            Some(("after", name_of_verb)) => Hook::After(verb(name_of_verb)?, name),
+            None if key == "talk" => Hook::After("talk", name),
+            _ => {
+                return Err(format!(
+                    "{}: use `before <verb>`, `after <verb>` or `talk`",
+                    location
+                ))
+            }
+        };
+        scripts.push(Script::compile(hook, source, location)?);
+    }
+    Ok(())
+}
+
+fn build_rule<F>(def: &RuleDef, path: &str, room_id: &F) -> Result<Rule, String>
+where
+    F: Fn(&str) -> Result<RoomID, String>,
+{
+    let verb = match &def.verb {
+        Some(verb) => Some(
+            find_verb(verb)
+                .filter(|known| known.takes_one_turn())
+                .map(|known| known.name)
+                .ok_or_else(|| format!("{}: there is no verb `{}`", path, verb))?,
+        ),
+        None => None,
+    };
+    let room = match &def.room {
+        Some(name) => Some(room_id(name).map_err(|e| format!("{}: {}", path, e))?),
+        None => None,
+    };
+    if let Some(redirect) = &def.redirect {
+        Parser::parse(redirect)
+            .map_err(|e| format!("{}: can't redirect to `{}`: {}", path, redirect, e))?;
+    }
+    let mut effects = vec![];
+    for effect in &def.effects {
+        effects.push(match effect {
+            EffectDef::GiveItem(name) => Effect::GiveItem(leak(name.clone())),
+            EffectDef::TakeItem(name) => Effect::TakeItem(leak(name.clone())),
+            EffectDef::SetFlag(flag) => Effect::SetFlag(leak(flag.clone())),
+            EffectDef::ClearFlag(flag) => Effect::ClearFlag(leak(flag.clone())),
+            EffectDef::OpenDoor(room, direction) => Effect::OpenDoor(
+                room_id(room).map_err(|e| format!("{}: {}", path, e))?,
+                leak(direction.clone()),
+            ),
+            EffectDef::Set(name, value) => Effect::Set(leak(name.clone()), value.clone()),
+            EffectDef::Add(name, by) => Effect::Add(leak(name.clone()), *by),
+            EffectDef::Follow => Effect::Follow,
+            EffectDef::Stay => Effect::Stay,
+        });
+    }
+    Ok(Rule {
+        name: leak(def.name.clone().unwrap_or_else(|| path.to_owned())),
+        phase: match def.phase {
+            PhaseDef::Before => Phase::Before,
+            PhaseDef::Instead => Phase::Instead,
+            PhaseDef::After => Phase::After,
+        },
+        verb,
+        object: def.object.clone().map(leak),
+        room,
+        when: def.when.clone().map(build_condition),
+        text: def.text.clone().map(leak),
+        effects,
+        veto: def.veto,
+        redirect: def.redirect.clone().map(leak),
+    })
+}
+
+/// Containers hold their contents by name, with the contents alongside them
+fn build_inventory(defs: &[ObjectDef]) -> Inventory {
+    let mut inventory = Inventory::default();
+    for def in defs {
+        inventory.add(Object {
+            name: leak(def.name.clone()),
+            desc: leak(def.desc.clone()),
+            variants: build_variants(&def.variants),
+            inventory: def
+                .contents
+                .iter()
+                .map(|content| content.name.clone())
+                .collect(),
+            accepts: def.accepts.clone(),
+            can_take: def.can_take,
+            scenery: def.scenery,
+            ..Default::default()
+        });
+        inventory
+            .objects
+            .extend(build_inventory(&def.contents).objects);
+    }
+    inventory
+}
+
+fn build_character(def: &CharacterDef) -> Character {
+    let dialogue = match &def.greeting {
+        Some(text) => Dialogue {
+            nodes: vec![DialogueNode {
+                text: leak(text.clone()),
+                ..Default::default()
+            }],
+            greetings: vec![NodeID(0)],
+            ..Default::default()
+        },
+        None => Dialogue::default(),
+    };
+    Character {
+        id: CharacterID::default(),
+        name: leak(def.name.clone()),
+        desc: leak(def.desc.clone()),
+        variants: build_variants(&def.variants),
+        inventory: build_inventory(&def.inventory),
+        dialogue,
+        trades: vec![],
+        msg_on_refuse: None,
+        schedule: Schedule::default(),
+        will_follow: def.will_follow,
+        is_following: false,
+        msg_on_follow: None,
+    }
+}
+
+fn build_variants(defs: &[VariantDef]) -> Vec<Variant> {
+    defs.iter()
+        .map(|def| Variant {
+            when: build_condition(def.when.clone()),
+            text: leak(def.text.clone()),
+        })
+        .collect()
+}
+
+fn build_condition(def: ConditionDef) -> Condition {
+    match def {
+        ConditionDef::Flag(flag) => Condition::Flag(leak(flag)),
+        ConditionDef::NotFlag(flag) => Condition::NotFlag(leak(flag)),
+        ConditionDef::PlayerHas(name) => Condition::PlayerHas(leak(name)),
+        ConditionDef::PlayerLacks(name) => Condition::PlayerLacks(leak(name)),
+        ConditionDef::FirstVisit => Condition::FirstVisit,
+        ConditionDef::After(time) => Condition::After(time),
+        ConditionDef::Before(time) => Condition::Before(time),
+        ConditionDef::Contains(container, content) => {
+            Condition::Contains(leak(container), leak(content))
+        }
+        ConditionDef::Present(name) => Condition::Present(leak(name)),
+        ConditionDef::Is(name, value) => Condition::Is(leak(name), value),
+        ConditionDef::AtLeast(name, min) => Condition::AtLeast(leak(name), min),
+        ConditionDef::Not(def) => Condition::Not(Box::new(build_condition(*def))),
+        ConditionDef::All(defs) => Condition::All(defs.into_iter().map(build_condition).collect()),
+        ConditionDef::Any(defs) => Condition::Any(defs.into_iter().map(build_condition).collect()),
+    }
+}
+
+/// The rules every world file follows: timed doors, schedules, scene triggers, the time limit and the goal
+fn update(
+    player: &mut Player,
+    rooms: &mut [Room],
+    world: &mut World,
+    state: &mut GameState,
+) -> Vec<String> {
+    let elapsed = state.clock.elapsed_secs();
+    for door in rooms.iter_mut().flat_map(|room| room.doors.iter_mut()) {
+        if door.opens_at.iter().any(|time| elapsed >= *time) {
+            door.is_open = true;
+        }
+    }
+    let messages = update_schedules(player, rooms, world, elapsed);
+
+    let (due, waiting): (Vec<_>, Vec<_>) = mem::take(&mut state.scene_triggers)
+        .into_iter()
+        .partition(|(when, _)| when.holds(player, rooms, state));
+    state.scene_triggers = waiting;
+    for (_, name) in due {
+        state.play_scene(name);
+    }
+
+    if state
+        .goal
+        .iter()
+        .any(|goal| goal.holds(player, rooms, state))
+    {
+        state.set_flag(WON_FLAG);
+    } else if state.time_limit.iter().any(|limit| elapsed >= *limit) {
+        state.set_flag(LOST_FLAG);
+    }
+    messages
+}
@@ -0,0 +1,566 @@
+use crate::character::Character;
+use crate::clock::Clock;
+use crate::condition::Condition;
+use crate::cutscene::{Cutscene, Cutscenes};
+use crate::description::Variant;
+use crate::dialogue::{Choice, Dialogue, DialogueNode, Effect, NodeID, Topic};
+use crate::door::Door;
+use crate::entity::Object;
+use crate::environment::Rewards;
+use crate::game::Game;
+use crate::game_state::{GameState, LOST_FLAG, WON_FLAG};
+use crate::history::History;
+use crate::inventory::Inventory;
+use crate::player::Player;
+use crate::room::{Room, RoomID};
+use crate::rules::{Phase, Rule, Rulebook};
+use crate::schedule::{update_schedules, Schedule, Stop};
+use crate::script::Scripts;
+use crate::world::{CharacterID, Location, PlayerID, World};
+use std::collections::HashMap;
+
+pub const GAME_LENGTH: u64 = 300;
+pub const DOOR_CLOSED_LENGTH: u64 = 180;
+pub const TEA_BREW_LENGTH: u64 = 60;
+const UNDO_DEPTH: usize = 20;
+
+const INTRO: &str = "============================
+Tea Time
+============================
+[pause 3]
+
+You slowly open your eyes.
+
+[pause 5]
+
+You've fallen asleep on your couch. You blink groggily as your eyes adjust to the darkness. Wait... it's dark outside? What time is it...
+
+[pause 5]
+
+You drag yourself up and turn on the lights. You're still really sleepy. A warm cup of tea sounds like the best thing in the world right now. You only have until 12am...
+
+[pause 5]";
+
+const WON: &str = "[pause 3]
+
+You look down at your tea and watch the sugar slowly disappear. You walk to your couch and sit down, waiting for it to cool down a bit.
+
+[pause 5]
+
+You take a sip of your tea -- it's perfectly bittersweet and fills you with warmth. You glance at your watch. It reads 12:00am.
+
+[pause 5]
+
+You close your eyes and listen to the gentle rainfall. Not long after, you drift off into sleep.
+
+[pause 5]
+============================
+THE END
+============================";
+
+const LOST: &str = "[pause 3]
+
+You get the sudden urge to stop in your tracks. Far away, a gong starts to ring.
+
+[pause 5]
+
+You glance at your watch. It reads 11:59pm. With each tick of the second hand, the gong sounds closer and closer.
+
+[pause 5]
+
+Finally, all hands of the watch meet. The ringing in your ears is unbearably loud. After what seems like an eternity, everything fades to black.
+
+[pause 5]
+============================
+THE END
+============================";
+
+fn get_living_room_items() -> Inventory {
+    Inventory {
+        objects: vec![
+            Object {
+                name: "couch",
+                desc: "A fluffy light grey couch. It's so comfortable that you sometimes unknowingly doze off on its cushions.",
+                scenery: true,
+                ..Default::default()
+            },
+            Object {
+                name: "coffee table",
+                desc: "The amber surface of the table is stained with faint traces of old coffee and tea mugs.",
+                inventory: vec!["watch".into()],
+                scenery: true,
+                ..Default::default()
+            },
+            Object {
+                name: "watch",
+                desc: "A simple analog watch with a thin gold band. You take comfort in the fact that if you ever need to know the time, you can USE the watch.",
+                variants: vec![Variant {
+                    when: Condition::PlayerHas("watch"),
+                    text: "A simple analog watch with a thin gold band. It reads {time}.",
+                }],
+                can_take: false,
+                can_use: true,
+                ..Default::default()
+            },
+        ],
+    }
+}
+
+fn get_kitchen_items() -> Inventory {
+    Inventory {
+        objects: vec![
+            Object {
+                name: "counter",
+                desc: "Your friendly philodendron sits idly on the countertop, keeping the remnants of this morning's half-eaten breakfast company. A kettle and a tea tin rest on the other side of the sink.",
+                inventory: vec!["water".into()],
+                scenery: true,
+                ..Default::default()
+            },
+            Object {
+                name: "kettle",
+                desc: "Your trusty electric kettle. Sees USE almost every morning, as well as some unfortunate nights.",
+                variants: vec![Variant {
+                    when: Condition::Contains("kettle", "water"),
+                    text: "Your trusty electric kettle, filled with water and ready to USE.",
+                }],
+                accepts: vec!["water".into()],
+                scenery: true,
+                ..Default::default()
+            },
+            Object {
+                name: "tea tin",
+                desc: "A delicate purple-hued tin box with a vaguely English air. The label reads `Harney & Son's Earl Grey Tea Sachets`. It also tells you that this is special tea and must be brewed for 60 minutes.",
+                variants: vec![
+                    Variant {
+                        when: Condition::Flag("tea brewed"),
+                        text: "A delicate purple-hued tin box with a vaguely English air. It's empty now, but it did its job.",
+                    },
+                    Variant {
+                        when: Condition::Not(Box::new(Condition::Contains("tea tin", "tea bag"))),
+                        text: "A delicate purple-hued tin box with a vaguely English air. It's empty now. The label reminds you that this special tea must be brewed for 60 minutes.",
+                    },
+                ],
+                inventory: vec!["tea bag".into()],
+                can_take: false,
+                scenery: true,
+                ..Default::default()
+            },
+            Object {
+                name: "tea bag",
+                desc: "A silk tea bag with dark leaves inside. The blueberry maple aroma of the tea comforts you.",
+                ..Default::default()
+            },
+            Object {
+                name: "water",
+                desc: "Water, the source of life! Straight from the Brita.",
+                ..Default::default()
+            },
+            Object {
+                name: "cupboard",
+                desc: "A white-framed cupboard. You can see your growing mug collection through the glass panes.",
+                inventory: vec!["mug".into()],
+                scenery: true,
+                ..Default::default()
+            },
+            Object {
+                name: "mug",
+                desc: "Your favorite mug. It fits snugly into your hand. A small outline of a rabbit is painted on the side.",
+                variants: vec![
+                    Variant {
+                        when: Condition::Contains("brewed tea", "sugar"),
+                        text: "A mug of sweet, perfectly brewed Earl Grey. A small outline of a rabbit is painted on the side.",
+                    },
+                    Variant {
+                        when: Condition::Flag("tea brewed"),
+                        text: "A mug of perfectly brewed Earl Grey. It could use a little sugar.",
+                    },
+                    Variant {
+                        when: Condition::Any(vec![
+                            Condition::Contains("mug", "hot water"),
+                            Condition::Contains("mug", "tea bag"),
+                        ]),
+                        text: "Your favorite mug, with a small rabbit painted on the side. It holds {contents}.",
+                    },
+                ],
+                accepts: vec!["tea bag".into(), "hot water".into(), "sugar".into()],
+                can_take: false,
+                ..Default::default()
+            },
+        ],
+    }
+}
+
+fn get_cat_dialogue() -> Dialogue {
+    Dialogue {
+        nodes: vec![
+            DialogueNode {
+                text: "You ask the cat if it can talk. It stares at you for a while, and just as you were about to give up, you hear it speak. `Hi, I suppose you're here for some sugar? Why don't you just ASK?`",
+                once: true,
+                ..Default::default()
+            },
+            DialogueNode {
+                text: "The cat seems to be preoccupied with trying to catch its own tail. You think it's best not to bother it.",
+                ..Default::default()
+            },
+            DialogueNode {
+                text: "The cat thinks for a moment. `I was saving this sugar for a special moment, but I guess this is as good as any.` The cat takes out some sugar cubes. `Good luck with your tea!`",
+                effects: vec![Effect::GiveItem("sugar")],
+                once: true,
+                ..Default::default()
+            },
+            DialogueNode {
+                text: "`Oh, they stepped out for a bit. They do that a lot.` The cat's tail flicks. `Do you want to know where they went?`",
+                choices: vec![
+                    Choice {
+                        text: "`Where did they go?`",
+                        next: NodeID(4),
+                        condition: None,
+                    },
+                    Choice {
+                        text: "`Never mind.`",
+                        next: NodeID(5),
+                        condition: None,
+                    },
+                ],
+                ..Default::default()
+            },
+            DialogueNode {
+                text: "`To the corner store, for more candles. They always come back at 10 sharp.`",
+                effects: vec![Effect::SetFlag("knows about owner")],
+                ..Default::default()
+            },
+            DialogueNode {
+                text: "The cat shrugs, which you didn't know cats could do.",
+                ..Default::default()
+            },
+            DialogueNode {
+                text: "`Your place? I suppose I could use a change of scenery.` The cat hops down from the couch and looks up at you expectantly.",
+                effects: vec![Effect::Follow],
+                ..Default::default()
+            },
+        ],
+        greetings: vec![NodeID(0), NodeID(1)],
+        topics: vec![
+            Topic {
+                name: "sugar",
+                nodes: vec![NodeID(2)],
+            },
+            Topic {
+                name: "owner",
+                nodes: vec![NodeID(3)],
+            },
+            Topic {
+                name: "home",
+                nodes: vec![NodeID(6)],
+            },
+        ],
+        msg_on_unknown_topic: Some("The cat yawns. It doesn't seem interested in that."),
+        ..Default::default()
+    }
+}
+
+fn get_rooms() -> Vec<Room> {
+    // living room 0, kitchen 1, hallway 2, cat room 3, stairwell 4
+    vec![
+        Room {
+            name: "Living Room",
+            desc: "Your small but cozy living room. A light grey couch is nestled into the far corner, and a coffee table sits comfortably at its feet. The front door of your apartment lies to the north, and the kitchen door lies to the east.",
+            variants: vec![Variant {
+                when: Condition::Not(Box::new(Condition::FirstVisit)),
+                text: "Your small but cozy living room. The couch cushions still hold the dent from your nap, and the coffee table sits comfortably at its feet. The front door of your apartment lies to the north, and the kitchen door lies to the east.",
+            }],
+            doors: vec![
+                Door {
+                    target: RoomID(1),
+                    direction: "east",
+                    ..Default::default()
+                },
+                Door {
+                    target: RoomID(2),
+                    direction: "north",
+                    msg_on_open: Some("You step into the hallway."),
+                    ..Default::default()
+                },
+            ],
+            inventory: get_living_room_items(),
+            characters: vec![],
+            ambience: Some("music.mp3"),
+        },
+        Room {
+            name: "Kitchen",
+            desc:
+                "A full moon glow illuminates the room from the window above the sink. Beside the window is a cupboard full of kitchenware. Various items lay on the kitchen counter. The door to the living room lies to the west.",
+            variants: vec![Variant {
+                when: Condition::After(150),
+                text: "The moon has slipped past the window above the sink, leaving only the glow of the streetlights. Beside the window is a cupboard full of kitchenware. Various items lay on the kitchen counter. The door to the living room lies to the west.",
+            }],
+            doors: vec![Door {
+                target: RoomID(0),
+                direction: "west",
+                ..Default::default()
+            }],
+            inventory: get_kitchen_items(),
+            characters: vec![],
+            ambience: Some("music.mp3"),
+        },
+        Room {
+            name: "Hallway",
+            desc: "The forest green walls of the corridor are decorated with black and white photos, eclectic paintings, and old 70s movie posters. It smells a bit musty. Your front door is to the south, across the hall is the door to Unit 11, and the stairwell is to the east.",
+            variants: vec![],
+            doors: vec![
+                Door {
+                    target: RoomID(0),
+                    direction: "south",
+                    ..Default::default()
+                },
+                Door {
+                    target: RoomID(3),
+                    direction: "north",
+                    is_open: false,
+                    msg_on_open: Some("You knock hesitantly. As soon as your hand makes contact with the door, it slowly creaks open."),
+                    msg_on_closed: Some("A note is on the door. It reads `I'll be back at 10:00pm.`"),
+                    sound_on_open: Some("door.mp3"),
+                    opens_at: Some(DOOR_CLOSED_LENGTH + 1),
+                    ..Default::default()
+                },
+                Door {
+                    target: RoomID(4),
+                    direction: "east",
+                    ..Default::default()
+                },
+            ],
+            inventory: Inventory::default(),
+            characters: vec![],
+            ambience: Some("rain.mp3"),
+        },
+        Room {
+            name: "Unit 11",
+            desc: "The living room in Unit 11 is dimly lit, the only source of light being the glow of a few candles. A worn leather couch sits against the far wall.",
+            variants: vec![],
+            doors: vec![Door {
+                target: RoomID(2),
+                direction: "south",
+                msg_on_open: Some("You step back into the hallway, and the door shuts softly behind you."),
+                ..Default::default()
+            }],
+            inventory: Inventory::default(),
+            characters: vec![Character {
+                id: CharacterID::default(),
+                name: "cat",
+                desc: "A medium-haired calico cat. It blinks slowly in your direction. You feel a bit silly, but you have the urge to talk to it.",
+                variants: vec![Variant {
+                    when: Condition::PlayerHas("sugar"),
+                    text: "A medium-haired calico cat. It eyes the sugar in your hand, then looks away as if it never cared.",
+                }],
+                inventory: Inventory {
+                    objects: vec![Object {
+                        name: "sugar",
+                        desc: "Small delicate sugar cubes. Each individual granule seems to shimmer and strangely reflect the light.",
+                        inventory: vec![],
+                        can_take: true,
+                        ..Default::default()
+                    }],
+                },
+                dialogue: get_cat_dialogue(),
+                trades: vec![],
+                msg_on_refuse: Some("The cat sniffs at it politely, then goes back to licking its paw."),
+                schedule: Schedule::default(),
+                will_follow: true,
+                is_following: false,
+                msg_on_follow: Some("The cat pads in behind you."),
+            }],
+            ambience: Some("music.mp3"),
+        },
+        Room {
+            name: "Stairwell",
+            desc: "A narrow stairwell with a flickering light bulb.",
+            variants: vec![],
+            doors: vec![Door {
+                target: RoomID(2),
+                direction: "west",
+                ..Default::default()
+            }],
+            inventory: Inventory::default(),
+            characters: vec![get_neighbor()],
+            ambience: Some("rain.mp3"),
+        },
+    ]
+}
+
+fn get_neighbor() -> Character {
+    Character {
+        id: CharacterID::default(),
+        name: "neighbor",
+        desc: "Your neighbor from Unit 11. Their coat is still damp from the rain.",
+        variants: vec![],
+        inventory: Inventory::default(),
+        // they're on their way home until 10:00pm, as the note on their door says
+        dialogue: Dialogue {
+            nodes: vec![
+                DialogueNode {
+                    text: "`Oh, hello! Just catching my breath. I'll be home by ten, if these stairs don't finish me off first.`",
+                    condition: Some(Condition::Before(DOOR_CLOSED_LENGTH)),
+                    ..Default::default()
+                },
+                DialogueNode {
+                    text: "`Oh, hello! Sorry, I was out getting more candles. I see you've met my cat.`",
+                    condition: Some(Condition::Present("cat")),
+                    ..Default::default()
+                },
+                DialogueNode {
+                    text: "`Oh, hello! Sorry, I was out getting more candles.`",
+                    ..Default::default()
+                },
+            ],
+            greetings: vec![NodeID(0), NodeID(1), NodeID(2)],
+            ..Default::default()
+        },
+        trades: vec![],
+        msg_on_refuse: None,
+        will_follow: false,
+        is_following: false,
+        msg_on_follow: None,
+        // the note on the door says they'll be back at 10:00pm
+        schedule: Schedule {
+            stops: vec![
+                Stop {
+                    time: DOOR_CLOSED_LENGTH,
+                    room: RoomID(2),
+                    msg_on_depart: None,
+                    msg_on_arrive: Some("Footsteps echo up the stairwell. Your neighbor from Unit 11 appears, a paper bag of candles tucked under one arm."),
+                },
+                Stop {
+                    time: DOOR_CLOSED_LENGTH + 1,
+                    room: RoomID(3),
+                    msg_on_depart: Some("Your neighbor unlocks the door to Unit 11 and slips inside, leaving it ajar."),
+                    msg_on_arrive: Some("The door creaks open and your neighbor steps inside, shaking the rain off their coat."),
+                },
+            ],
+            next: 0,
+        },
+    }
+}
+
+fn update(
+    player: &mut Player,
+    rooms: &mut [Room],
+    world: &mut World,
+    state: &mut GameState,
+) -> Vec<String> {
+    let mut messages = vec![];
+
+    // check time-based events
+    let duration = state.clock.elapsed_secs();
+    for door in rooms.iter_mut().flat_map(|room| room.doors.iter_mut()) {
+        if door.opens_at.iter().any(|time| duration >= *time) {
+            door.is_open = true;
+        }
+    }
+    if duration >= GAME_LENGTH && !state.player_lost() {
+        state.set_flag(LOST_FLAG);
+        state.play_sound("gong.mp3");
+    }
+    messages.extend(update_schedules(player, rooms, world, duration));
+
+    let carried = Location::Player(player.id);
+    if let Some(mug_id) = world.object_named(carried, "mug") {
+        let mug = world.object_mut(mug_id, player, rooms).unwrap();
+        // check brew time on tea
+        if let Some(time) = state.vars.time("tea time") {
+            if duration.saturating_sub(time) > TEA_BREW_LENGTH
+                && mug.inventory.contains(&"hot water".to_string())
+                && mug.inventory.contains(&"tea bag".to_string())
+            {
+                for object_name in &["hot water", "tea bag"] {
+                    let index = mug.inventory.iter().position(|o| o == object_name);
+                    if let Some(i) = index {
+                        mug.inventory.remove(i);
+                    }
+                    mug.inventory.push("brewed tea".to_string());
+                }
+                world.rename_object(mug_id, "brewed tea", player, rooms);
+                state.set_flag("tea brewed");
+                messages.push("Your tea is brewed, but you would really like some sugar. Maybe your neighbor in Unit 11 has some...".to_owned())
+            }
+        }
+    }
+
+    let brewed_tea = world
+        .object_named(carried, "brewed tea")
+        .and_then(|id| world.object(id, player, rooms));
+    if let Some(tea) = brewed_tea {
+        // check win condition
+        if tea.inventory.contains(&"sugar".to_owned()) {
+            state.set_flag(WON_FLAG);
+        }
+    }
+
+    messages
+}
+
+fn rules() -> Rulebook {
+    Rulebook {
+        rules: vec![Rule {
+            name: "taking the sugar while the cat watches",
+            phase: Phase::Instead,
+            verb: Some("take"),
+            object: Some("sugar"),
+            when: Some(Condition::All(vec![
+                Condition::Present("cat"),
+                Condition::PlayerLacks("sugar"),
+            ])),
+            text: Some("You reach for the sugar. The cat hisses and swats your hand away. Perhaps you could just ask."),
+            ..Default::default()
+        }],
+    }
+}
+
+fn cutscenes() -> Cutscenes {
+    Cutscenes {
+        intro: Some(Cutscene::parse(INTRO).unwrap()),
+        won: Some(Cutscene::parse(WON).unwrap()),
+        lost: Some(Cutscene::parse(LOST).unwrap()),
+        ..Default::default()
+    }
+}
+
+pub fn new_game(clock: Clock) -> Game {
+    let mut player = Player {
+        id: PlayerID(0),
+        name: "me",
+        desc: "a person",
+        at: RoomID(0),
+        inventory: Inventory::default(),
+        visits: HashMap::new(),
+        came_from: None,
+        seen: HashMap::new(),
+    };
+    let mut rooms = get_rooms();
+    let world = World::new(&mut player, &mut rooms);
+    let mut state = GameState::new(clock);
+    state.time_limit = Some(GAME_LENGTH);
+    Game {
+        player,
+        rooms,
+        state,
+        history: History::new(UNDO_DEPTH),
+        update,
+        log: vec![],
+        cutscenes: cutscenes(),
+        world,
+        scripts: Scripts::default(),
+        rules: rules(),
+        debug: false,
+    }
+}
+
+/// Rewards for agents: exploring, brewing the tea, and getting it sweetened by midnight
+pub fn rewards() -> Rewards {
+    Rewards {
+        new_room: 1.0,
+        flags: vec![("tea brewed", 5.0), ("knows about owner", 1.0)],
+        won: 10.0,
+        lost: -10.0,
+        per_turn: -0.01,
+    }
+}
@@ -0,0 +1,49 @@
+use text_engine::audio::{Audio, Cue, RecordingBackend};
+use text_engine::clock::Clock;
+use text_engine::worlds::tea_time;
+
+#[test]
+fn rooms_and_events_cue_sounds() {
+    let recording = RecordingBackend::default();
+    let mut audio = Audio::new(Box::new(recording.clone()));
+    let mut game = tea_time::new_game(Clock::manual());
+    audio.update(&game);
+    assert_eq!(recording.take(), vec![Cue::Loop("music.mp3")]);
+
+    // the kitchen plays the same music, so nothing changes
+    game.step("east");
+    audio.update(&game);
+    assert_eq!(recording.take(), vec![]);
+
+    for input in &[
+        "examine counter",
+        "take water",
+        "put water in kettle",
+        "use kettle",
+    ] {
+        game.step(input);
+        audio.update(&game);
+    }
+    assert_eq!(recording.take(), vec![Cue::Once("kettle.mp3")]);
+
+    game.step("west");
+    game.step("north");
+    audio.update(&game);
+    assert_eq!(recording.take(), vec![Cue::Loop("rain.mp3")]);
+
+    assert_eq!(
+        audio.command("volume 4", &game),
+        Some("Volume set to 4.".to_owned())
+    );
+    assert_eq!(
+        audio.command("music off", &game),
+        Some("Music off.".to_owned())
+    );
+    assert_eq!(audio.command("look", &game), None);
+    assert_eq!(recording.take(), vec![Cue::Volume(0.4), Cue::StopLoop]);
+
+    // effects still play with the music off
+    game.step("wait 300 minutes");
+    audio.update(&game);
+    assert_eq!(recording.take(), vec![Cue::Once("gong.mp3")]);
+}
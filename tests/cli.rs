@@ -0,0 +1,112 @@
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+use text_engine::cli::{ClockKind, Format, Options};
+use text_engine::clock::Clock;
+use text_engine::save::Save;
+use text_engine::worlds::tea_time;
+
+fn parse(args: &[&str]) -> Result<Options, String> {
+    Options::parse(args.iter().map(|arg| arg.to_string()))
+}
+
+#[test]
+fn parses_flags() {
+    assert_eq!(parse(&[]).unwrap(), Options::default());
+    let options = parse(&[
+        "--world",
+        "cellar.json",
+        "--no-audio",
+        "--no-delay",
+        "--speed",
+        "2.5",
+        "--clock",
+        "turns",
+        "--seed",
+        "42",
+        "--script",
+        "moves.txt",
+        "--format",
+        "jsonl",
+        "--debug",
+    ])
+    .unwrap();
+    assert_eq!(options.world, Some(PathBuf::from("cellar.json")));
+    assert!(!options.audio && !options.delay);
+    assert_eq!(options.speed, 2.5);
+    assert_eq!(options.clock, ClockKind::Turns);
+    assert_eq!(options.seed, Some(42));
+    assert_eq!(options.script, Some(PathBuf::from("moves.txt")));
+    assert_eq!(options.format, Format::Jsonl);
+    assert!(options.debug);
+    assert_eq!(
+        parse(&["--protocol", "jsonl"]).unwrap().format,
+        Format::Jsonl
+    );
+}
+
+#[test]
+fn rejects_bad_flags() {
+    assert_eq!(parse(&["--seed"]).unwrap_err(), "--seed needs a value");
+    assert_eq!(
+        parse(&["--clock", "sundial"]).unwrap_err(),
+        "unknown clock `sundial`"
+    );
+    assert_eq!(
+        parse(&["--colour"]).unwrap_err(),
+        "unknown option `--colour`"
+    );
+    assert!(parse(&["--seed", "-1"]).is_err());
+    assert!(parse(&["--speed", "0"]).is_err());
+    assert!(parse(&["--tui", "--script", "moves.txt"]).is_err());
+}
+
+#[test]
+fn saves_and_resumes() {
+    let mut game = tea_time::new_game(Clock::manual());
+    game.state.reseed(3);
+    game.step("east");
+    game.state.clock.advance(Duration::from_secs(40));
+    game.step("take kettle");
+    game.step("undo");
+    game.step("take mug");
+
+    let path = std::env::temp_dir().join("text_engine_cli_test.save");
+    Save::of(&game, None).write(&path).unwrap();
+    let save = Save::read(&path).unwrap();
+    assert_eq!(save.elapsed, 40);
+    assert_eq!(save.commands.len(), 4);
+
+    // a realtime clock picks up the saved time too
+    let mut resumed = tea_time::new_game(Clock::realtime());
+    save.replay(&mut resumed);
+    assert_eq!(resumed.state.seed, 3);
+    assert_eq!(resumed.state.clock.elapsed_secs(), 40);
+    assert_eq!(resumed.describe_room(), game.describe_room());
+    let names = |game: &text_engine::game::Game| {
+        let objects = &game.player.inventory.objects;
+        objects.iter().map(|object| object.name).collect::<Vec<_>>()
+    };
+    assert_eq!(names(&resumed), names(&game));
+    assert!(!resumed.player.has("kettle"));
+    assert_eq!(resumed.log, game.log);
+}
+
+#[test]
+fn the_game_stops_when_input_runs_out() {
+    let mut game = Command::new(env!("CARGO_BIN_EXE_game"))
+        .args(["--no-audio", "--no-delay"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    game.stdin.take().unwrap().write_all(b"east\n").unwrap();
+    let (done, finished) = mpsc::channel();
+    thread::spawn(move || done.send(game.wait_with_output().unwrap()));
+    let output = finished.recv_timeout(Duration::from_secs(20)).unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Kitchen"));
+}
@@ -0,0 +1,46 @@
+use text_engine::actions::complete;
+use text_engine::clock::Clock;
+use text_engine::worlds::tea_time;
+
+#[test]
+fn completes_verbs_objects_and_open_doors() {
+    let mut game = tea_time::new_game(Clock::manual());
+    assert_eq!(complete(&game, "ex"), (0, vec!["examine".to_owned()]));
+    assert_eq!(complete(&game, "go e"), (3, vec!["east".to_owned()]));
+
+    // Unit 11 is locked until the neighbor gets back
+    game.step("north");
+    assert_eq!(complete(&game, "go n"), (4, vec![]));
+
+    game.step("south");
+    game.step("east");
+    assert_eq!(
+        complete(&game, "take te"),
+        (5, vec!["tea bag".to_owned(), "tea tin".to_owned()])
+    );
+    assert_eq!(
+        complete(&game, "put tea b"),
+        (4, vec!["tea bag".to_owned()])
+    );
+}
+
+#[test]
+fn completes_every_verb_in_the_table() {
+    let game = tea_time::new_game(Clock::manual());
+    assert_eq!(complete(&game, "go"), (0, vec!["go to".to_owned()]));
+    assert_eq!(complete(&game, "ret"), (0, vec!["return".to_owned()]));
+    assert_eq!(
+        complete(&game, "u"),
+        (0, vec!["undo".to_owned(), "use".to_owned()])
+    );
+}
+
+#[test]
+fn aliases_come_from_the_same_table() {
+    let mut game = tea_time::new_game(Clock::manual());
+    game.step("e");
+    game.step("travel  to   living room");
+    assert_eq!(game.player.get_curr_room(&game.rooms).name, "Living Room");
+    game.step("go back");
+    assert_eq!(game.player.get_curr_room(&game.rooms).name, "Kitchen");
+}
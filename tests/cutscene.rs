@@ -0,0 +1,151 @@
+use std::time::Duration;
+use text_engine::clock::Clock;
+use text_engine::cutscene::{Beat, Cutscene, Renderer};
+use text_engine::protocol;
+use text_engine::worlds::{file, tea_time};
+
+/// Writes down what a scene asked for, and skips at the pause it's told to
+#[derive(Default)]
+struct Recorder {
+    played: Vec<String>,
+    skip_at: Option<usize>,
+    pauses: usize,
+}
+
+impl Renderer for Recorder {
+    fn text(&mut self, line: &str) {
+        self.played.push(line.to_owned());
+    }
+
+    fn sound(&mut self, sound: &'static str) {
+        self.played.push(format!("<{}>", sound));
+    }
+
+    fn pause(&mut self, duration: Duration) -> bool {
+        self.played.push(format!("<{}ms>", duration.as_millis()));
+        self.pauses += 1;
+        self.skip_at == Some(self.pauses)
+    }
+
+    fn wait_for_key(&mut self) -> bool {
+        self.played.push("<key>".to_owned());
+        false
+    }
+}
+
+#[test]
+fn parses_markup() {
+    let scene =
+        Cutscene::parse("Dawn.\n[pause 1.5]\n\n[sound bell.mp3]\n[wait]\n[Not a directive]")
+            .unwrap();
+    assert_eq!(
+        scene.beats,
+        vec![
+            Beat::Text("Dawn."),
+            Beat::Pause(Duration::from_millis(1500)),
+            Beat::Text(""),
+            Beat::Sound("bell.mp3"),
+            Beat::WaitForKey,
+            Beat::Text("[Not a directive]"),
+        ]
+    );
+    assert_eq!(scene.text(), "Dawn.\n\n[Not a directive]");
+
+    assert_eq!(
+        Cutscene::parse("Hi\n[pause soon]").unwrap_err(),
+        "line 2: `soon` isn't a number of seconds"
+    );
+    for pause in &["inf", "1e40", "-1", "NaN"] {
+        let markup: &'static str = Box::leak(format!("[pause {}]", pause).into_boxed_str());
+        assert_eq!(
+            Cutscene::parse(markup).unwrap_err(),
+            format!("line 1: `{}` isn't a number of seconds", pause)
+        );
+    }
+    assert_eq!(
+        Cutscene::parse("[sound]").unwrap_err(),
+        "line 1: `[sound]` needs an argument"
+    );
+}
+
+#[test]
+fn plays_at_speed_and_skips() {
+    let scene =
+        Cutscene::parse("One\n[pause 2]\n[sound a.mp3]\nTwo\n[pause 2]\n[wait]\nThree").unwrap();
+    let mut recorder = Recorder::default();
+    scene.play(&mut recorder, 2.0);
+    assert_eq!(
+        recorder.played,
+        vec!["One", "<1000ms>", "<a.mp3>", "Two", "<1000ms>", "<key>", "Three"]
+    );
+
+    // after a skip the rest of the text comes all at once
+    let mut recorder = Recorder {
+        skip_at: Some(1),
+        ..Default::default()
+    };
+    scene.play(&mut recorder, 1.0);
+    assert_eq!(recorder.played, vec!["One", "<2000ms>", "Two", "Three"]);
+}
+
+#[test]
+fn worlds_declare_scenes() {
+    let game = tea_time::new_game(Clock::manual());
+    let intro = game.cutscenes.intro.as_ref().unwrap();
+    assert!(intro
+        .text()
+        .starts_with("============================\nTea Time\n"));
+    assert!(intro.beats.contains(&Beat::Pause(Duration::from_secs(5))));
+    assert!(game
+        .cutscenes
+        .won
+        .as_ref()
+        .unwrap()
+        .text()
+        .ends_with("THE END\n============================"));
+
+    let world = r#"{
+        "rooms": [{ "name": "Attic", "desc": "Dusty.", "objects": [{ "name": "bell", "desc": "Brass.", "can_take": true }] }],
+        "intro": "Up here again.",
+        "scenes": { "ring": "[sound bell.mp3]\nThe bell rings by itself." },
+        "triggers": [{ "when": { "player_has": "bell" }, "scene": "ring" }]
+    }"#;
+    let mut game = file::parse(world, Clock::manual()).unwrap();
+    assert_eq!(
+        game.cutscenes.intro.as_ref().unwrap().text(),
+        "Up here again."
+    );
+    assert!(game
+        .cutscenes
+        .lost
+        .as_ref()
+        .unwrap()
+        .text()
+        .contains("You have lost."));
+    game.step("take bell");
+    assert_eq!(game.cued_scenes().len(), 1);
+    game.step("look");
+    assert!(game.cued_scenes().is_empty());
+
+    // frontends that can't pace scenes get them as text
+    let mut game = file::parse(world, Clock::manual()).unwrap();
+    let response = protocol::respond(&mut game, "\"take bell\"");
+    assert_eq!(response["messages"][1], "The bell rings by itself.");
+
+    let error = file::parse(
+        r#"{ "rooms": [{ "name": "A", "desc": "" }], "won": "[pause]" }"#,
+        Clock::manual(),
+    );
+    assert_eq!(
+        error.err().unwrap(),
+        "won: line 1: `[pause]` needs an argument"
+    );
+}
+
+#[test]
+fn pauses_too_long_to_count_wait_for_a_key() {
+    let scene = Cutscene::parse("[pause 1e10]").unwrap();
+    let mut recorder = Recorder::default();
+    scene.play(&mut recorder, f32::MIN_POSITIVE);
+    assert_eq!(recorder.played, vec!["<key>"]);
+}
@@ -0,0 +1,119 @@
+use text_engine::clock::Clock;
+use text_engine::game::Game;
+use text_engine::save::Save;
+use text_engine::worlds::tea_time;
+
+fn debug_game() -> Game {
+    let mut game = tea_time::new_game(Clock::manual());
+    game.debug = true;
+    game
+}
+
+#[test]
+fn debug_commands_are_off_by_default() {
+    let mut game = tea_time::new_game(Clock::manual());
+    assert_eq!(
+        game.step("@goto unit 11"),
+        vec!["Debug commands are off. Start the game with --debug to use them."]
+    );
+    assert_eq!(game.player.get_curr_room(&game.rooms).name, "Living Room");
+    assert!(game.log.is_empty());
+}
+
+#[test]
+fn writers_can_skip_ahead() {
+    let mut game = debug_game();
+    let events = game.step("@events");
+    let lines: Vec<&str> = events[0].lines().collect();
+    assert_eq!(
+        lines[..3],
+        [
+            "180 (10:00pm): the neighbor goes to Hallway",
+            "181 (10:01pm): the door north of Hallway opens",
+            "181 (10:01pm): the neighbor goes to Unit 11"
+        ]
+    );
+    assert!(lines[3].starts_with("300 ") && lines[3].ends_with(": time runs out"));
+    let messages = game.step("@clock 181");
+    assert_eq!(messages[0], "[The clock now reads 10:01pm.]");
+    assert!(game.step("@events")[0].starts_with("300 "));
+
+    assert!(game.step("@goto unit 11")[0].contains("Unit 11"));
+    let sugar = game
+        .world
+        .character_named(game.player.at, "cat")
+        .and_then(|cat| {
+            let cat = game.world.character(cat, &game.rooms)?;
+            Some(cat.inventory.find_object("sugar")?.id.0)
+        });
+    assert_eq!(
+        game.step("@inventory cat"),
+        vec![format!("sugar (#{})", sugar.unwrap())]
+    );
+    game.step("@spawn spoon");
+    assert!(game
+        .step("take spoon")
+        .contains(&"You take the spoon.".to_owned()));
+    assert_eq!(game.step("@remove spoon"), vec!["[Removed the spoon.]"]);
+    assert!(!game.player.has("spoon"));
+
+    game.step("@flag met cat");
+    game.step("@set spoons 3");
+    assert!(game.state.has_flag("met cat"));
+    assert_eq!(game.state.vars.int("spoons"), 3);
+    assert_eq!(game.step("@vars"), vec!["met cat = true\nspoons = 3"]);
+    assert_eq!(
+        game.step("@set note  left by the door"),
+        vec!["[note = \"left by the door\"]"]
+    );
+    assert_eq!(game.state.vars.string("note"), Some("left by the door"));
+}
+
+#[test]
+fn debug_commands_show_the_last_turn() {
+    let mut game = debug_game();
+    game.step("@goto unit 11");
+    game.step("take sugar");
+    // looking doesn't count as a turn, so the trace is still there
+    assert_eq!(game.step("@state").len(), 1);
+    assert_eq!(
+        game.step("@rules"),
+        vec!["taking the sugar while the cat watches"]
+    );
+    game.step("look");
+    assert_eq!(game.step("@rules"), vec!["No rules fired last turn."]);
+    assert!(game.step("@state")[0].contains("room: Unit 11"));
+    assert_eq!(
+        game.step("@teleport"),
+        vec!["[Unknown debug command `@teleport`. Try @help.]"]
+    );
+}
+
+#[test]
+fn saves_replay_debug_commands() {
+    let mut game = debug_game();
+    game.step("@goto hallway");
+    game.step("@advance 30");
+    let save = Save::of(&game, None);
+
+    let mut resumed = debug_game();
+    save.replay(&mut resumed);
+    assert_eq!(resumed.describe_room(), game.describe_room());
+
+    // without --debug they're left out
+    let mut resumed = tea_time::new_game(Clock::manual());
+    save.replay(&mut resumed);
+    assert!(!resumed.debug);
+    assert_ne!(resumed.describe_room(), game.describe_room());
+}
+
+#[test]
+fn the_clock_only_goes_forwards() {
+    let mut game = debug_game();
+    game.step("@clock 100");
+    assert_eq!(
+        game.step("@clock 40"),
+        vec!["[The clock can't go back. It reads 8:40pm.]"]
+    );
+    assert_eq!(game.state.clock.elapsed_secs(), 100);
+}
@@ -0,0 +1,66 @@
+use std::time::Duration;
+use text_engine::clock::Clock;
+use text_engine::worlds::{file, tea_time};
+
+#[test]
+fn descriptions_change_with_the_game() {
+    let mut game = tea_time::new_game(Clock::manual());
+    assert!(game.step("examine coffee table")[0].starts_with("The amber surface"));
+    game.step("take watch");
+    game.state.clock.advance(Duration::from_secs(65));
+    assert_eq!(
+        game.step("examine watch"),
+        vec!["A simple analog watch with a thin gold band. It reads 8:05pm."]
+    );
+
+    game.step("east");
+    assert!(game.step("examine tea tin")[0].contains("must be brewed for 60 minutes"));
+    game.step("take tea bag");
+    assert!(game.step("examine tea tin")[0].contains("It's empty now."));
+    game.step("examine cupboard");
+    game.step("take mug");
+    game.step("put tea bag in mug");
+    assert!(game.step("examine mug")[0].ends_with("It holds tea bag."));
+
+    // the moon has moved by half past nine
+    assert!(game.step("look")[0].starts_with("A full moon glow"));
+    game.state.clock.advance(Duration::from_secs(90));
+    assert!(game.step("look")[0].starts_with("The moon has slipped"));
+
+    // coming back to where the game started isn't the first visit
+    let living_room = game.step("west").join("\n");
+    assert!(
+        living_room.contains("still hold the dent from your nap"),
+        "{}",
+        living_room
+    );
+}
+
+#[test]
+fn world_files_have_variants() {
+    let world = r#"{
+        "rooms": [{
+            "name": "Shed",
+            "desc": "A shed. The box holds {contents of box}.",
+            "variants": [{ "when": { "not": "first_visit" }, "text": "The shed again." }],
+            "doors": [{ "direction": "out", "to": "Yard" }],
+            "objects": [{
+                "name": "box",
+                "desc": "A box.",
+                "variants": [{ "when": { "contains": ["box", "key"] }, "text": "A box with {contents} in it, at {time}." }],
+                "contents": [{ "name": "key", "desc": "A key." }, { "name": "coin", "desc": "A coin." }]
+            }]
+        }, {
+            "name": "Yard",
+            "desc": "A yard.",
+            "doors": [{ "direction": "in", "to": "Shed" }]
+        }]
+    }"#;
+    let mut game = file::parse(world, Clock::manual()).unwrap();
+    assert!(game.describe_room().contains("The box holds key and coin."));
+    assert!(game.step("examine box")[0].starts_with("A box with key and coin in it, at 7:00pm.\n"));
+    game.step("take key");
+    assert!(game.step("examine box")[0].starts_with("A box.\n"));
+    game.step("out");
+    assert!(game.step("in")[0].contains("The shed again."));
+}
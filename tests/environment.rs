@@ -0,0 +1,53 @@
+use std::fs;
+use text_engine::environment::Environment;
+use text_engine::game::Ending;
+use text_engine::transcript::Transcript;
+use text_engine::worlds::tea_time;
+
+#[test]
+fn episodes_follow_their_seed() {
+    let mut env = Environment::new(tea_time::new_game, tea_time::rewards());
+    let mut rolls = |seed| {
+        env.reset(seed);
+        let mut state = env.game().state.clone();
+        (0..8).map(|_| state.random(100)).collect::<Vec<_>>()
+    };
+    assert_eq!(rolls(7), rolls(7));
+    assert_ne!(rolls(7), rolls(8));
+}
+
+#[test]
+fn rewards_exploring_and_winning() {
+    let mut env = Environment::new(tea_time::new_game, tea_time::rewards());
+    let (observation, info) = env.reset(7);
+    assert!(observation.contains("Living Room"));
+    assert_eq!(info.seed, 7);
+    assert!(info.valid_actions.contains(&"east".to_owned()));
+
+    let (_, reward, done, info) = env.step("east");
+    assert_eq!(reward, 1.0 - 0.01);
+    assert!(!done);
+    assert_eq!(info.room, "Kitchen");
+    assert_eq!(info.elapsed, 1);
+
+    // coming back is not new
+    let (_, reward, _, _) = env.step("west");
+    assert_eq!(reward, -0.01);
+
+    let walkthrough = fs::read_to_string("tests/transcripts/walkthrough.txt").unwrap();
+    let walkthrough = Transcript::parse(&walkthrough);
+    env.reset(7);
+    let mut total = 0.0;
+    let mut done = false;
+    for action in walkthrough.inputs() {
+        let (_, reward, finished, _) = env.step(action);
+        total += reward;
+        done = finished;
+    }
+    assert!(done);
+    assert_eq!(env.game().ending(), Some(Ending::Won));
+    assert!(total > 10.0 + 5.0);
+
+    let (_, reward, done, _) = env.step("look");
+    assert_eq!((reward, done), (0.0, true));
+}
@@ -0,0 +1,42 @@
+use text_engine::clock::Clock;
+use text_engine::worlds::tea_time;
+
+#[test]
+fn map_shows_where_the_player_has_been() {
+    let mut game = tea_time::new_game(Clock::manual());
+    assert_eq!(
+        game.step("map"),
+        vec!["       ?\n[*Living Room*]-?\n\n*: you are here   ?: unexplored"]
+    );
+    game.step("east");
+    game.step("west");
+    game.step("north");
+    assert_eq!(
+        game.step("map"),
+        vec!["      ?\n [*Hallway*]-?\n      |\n[Living Room]-----[Kitchen]\n\n*: you are here   ?: unexplored"]
+    );
+}
+
+#[test]
+fn brief_modes_cut_room_descriptions() {
+    let mut game = tea_time::new_game(Clock::manual());
+    let full = game.step("east")[0].clone();
+    game.step("brief");
+    // a first visit is still described in full
+    game.step("west");
+    assert!(game.step("north")[0].contains("The forest green walls"));
+    game.step("south");
+    assert_eq!(
+        game.step("east"),
+        vec!["===============\nKitchen\n===============\nExits: west."]
+    );
+
+    game.step("west");
+    game.step("superbrief");
+    assert!(!game.step("east")[0].contains("full moon"));
+    assert!(game.step("look")[0].contains("full moon"));
+
+    game.step("verbose");
+    game.step("west");
+    assert_eq!(game.step("east")[0], full);
+}
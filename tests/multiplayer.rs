@@ -0,0 +1,144 @@
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+use text_engine::clock::Clock;
+use text_engine::game_state::GameState;
+use text_engine::multiplayer::{Message, PlayerID, SharedWorld};
+use text_engine::player::Player;
+use text_engine::room::Room;
+use text_engine::world::World;
+use text_engine::worlds::{file, tea_time};
+
+fn said_to(messages: &[Message], to: PlayerID) -> Vec<&str> {
+    messages
+        .iter()
+        .filter(|msg| msg.to == to)
+        .map(|msg| msg.text.as_str())
+        .collect()
+}
+
+#[test]
+fn two_players_share_the_kitchen() {
+    let mut world = SharedWorld::new(tea_time::new_game(Clock::manual()));
+    let (sam, _) = world.join("Sam");
+    let (alex, messages) = world.join("Alex");
+    assert_eq!(said_to(&messages, sam), vec!["Alex arrives."]);
+    assert!(said_to(&messages, alex)[0].ends_with("Sam is here."));
+
+    let messages = world.step(sam, "east");
+    assert_eq!(said_to(&messages, alex), vec!["Sam leaves to the east."]);
+    let messages = world.step(alex, "east");
+    assert_eq!(said_to(&messages, sam), vec!["Alex arrives."]);
+    assert!(said_to(&messages, alex).contains(&"Sam is here."));
+
+    world.step(alex, "examine cupboard");
+    let messages = world.step(sam, "take mug");
+    assert_eq!(said_to(&messages, sam), vec!["You take the mug."]);
+    assert_eq!(said_to(&messages, alex), vec!["Sam takes the mug."]);
+    let messages = world.step(alex, "take mug");
+    assert_ne!(said_to(&messages, alex), vec!["You take the mug."]);
+    assert!(world.player(sam).unwrap().has("mug"));
+    assert!(!world.player(alex).unwrap().has("mug"));
+
+    let messages = world.step(alex, "Say Can I have that?");
+    assert_eq!(
+        said_to(&messages, sam),
+        vec!["Alex says, \"Can I have that?\""]
+    );
+
+    // leaving puts the mug back within reach
+    let messages = world.leave(sam);
+    assert_eq!(said_to(&messages, alex), vec!["Sam has left."]);
+    world.step(alex, "take mug");
+    assert!(world.player(alex).unwrap().has("mug"));
+}
+
+#[test]
+fn each_player_reaches_only_their_own_things() {
+    let mut world = SharedWorld::new(tea_time::new_game(Clock::manual()));
+    let (sam, _) = world.join("Sam");
+    let (alex, _) = world.join("Alex");
+    world.step(sam, "east");
+    world.step(alex, "east");
+    world.step(sam, "examine cupboard");
+    world.step(sam, "take mug");
+
+    let messages = world.step(alex, "drop mug");
+    assert_ne!(said_to(&messages, alex), vec!["You drop the mug."]);
+    assert!(world.player(sam).unwrap().has("mug"));
+    let messages = world.step(sam, "drop mug");
+    assert_eq!(said_to(&messages, sam), vec!["You drop the mug."]);
+    world.step(alex, "take mug");
+    assert!(world.player(alex).unwrap().has("mug"));
+}
+
+#[test]
+fn seats_are_reused_after_a_player_leaves() {
+    let mut world = SharedWorld::new(tea_time::new_game(Clock::manual()));
+    let (sam, _) = world.join("Sam");
+    let (alex, _) = world.join("Alex");
+    world.leave(sam);
+    let (kim, messages) = world.join("Kim");
+    assert_eq!(kim, sam);
+    assert_eq!(world.name(kim), Some("Kim"));
+    assert_eq!(said_to(&messages, alex), vec!["Kim arrives."]);
+    assert_eq!(world.players().count(), 2);
+}
+
+#[test]
+fn the_world_moves_on_once_per_turn() {
+    static UPDATES: AtomicUsize = AtomicUsize::new(0);
+    fn count(_: &mut Player, _: &mut [Room], _: &mut World, _: &mut GameState) -> Vec<String> {
+        UPDATES.fetch_add(1, Ordering::SeqCst);
+        vec![]
+    }
+    let mut world = SharedWorld::new(tea_time::new_game(Clock::manual()));
+    world.update = count;
+    let (sam, _) = world.join("Sam");
+    world.join("Alex");
+    world.join("Kim");
+    world.step(sam, "look");
+    assert_eq!(UPDATES.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn the_clock_stops_while_nobody_is_playing() {
+    let pause = Duration::from_millis(200);
+    let mut world = SharedWorld::new(tea_time::new_game(Clock::realtime()));
+    thread::sleep(pause);
+    let (sam, _) = world.join("Sam");
+    assert!(world.state.clock.elapsed() < pause);
+
+    world.leave(sam);
+    thread::sleep(pause);
+    world.join("Alex");
+    assert!(world.state.clock.elapsed() < pause);
+}
+
+#[test]
+fn saying_nothing_asks_what_to_say() {
+    let mut world = SharedWorld::new(tea_time::new_game(Clock::manual()));
+    let (sam, _) = world.join("Sam");
+    let (alex, _) = world.join("Alex");
+    let messages = world.step(sam, "  SAY ");
+    assert_eq!(said_to(&messages, sam), vec!["Say what?"]);
+    assert!(said_to(&messages, alex).is_empty());
+}
+
+#[test]
+fn rules_and_scripts_run_for_every_player() {
+    let vault = file::load(Path::new("tests/worlds/vault.json"), Clock::manual()).unwrap();
+    let mut world = SharedWorld::new(vault);
+    let (sam, _) = world.join("Sam");
+    let (alex, _) = world.join("Alex");
+    let messages = world.step(sam, "take purse");
+    assert_eq!(
+        said_to(&messages, sam),
+        vec!["You take the purse.", "Coins: 2"]
+    );
+    // the rule for the coin brings the count up to what the guard wants
+    world.step(alex, "take coin");
+    let messages = world.step(alex, "north");
+    assert!(said_to(&messages, alex)[0].contains("Gold everywhere."));
+}
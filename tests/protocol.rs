@@ -0,0 +1,58 @@
+use serde_json::Value;
+use std::io::Cursor;
+use std::time::Duration;
+use text_engine::clock::Clock;
+use text_engine::protocol;
+use text_engine::worlds::tea_time;
+
+#[test]
+fn jsonl_session() {
+    let mut game = tea_time::new_game(Clock::manual());
+    let input =
+        "\"examine coffee table\"\n\"take watch\"\n{\"command\": \"go east\"}\n{}\nnot json\n";
+    let mut output = vec![];
+    protocol::run(&mut game, Cursor::new(input), &mut output).unwrap();
+    let responses: Vec<Value> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(responses.len(), 6);
+
+    let opening = &responses[0];
+    assert_eq!(opening["room"]["name"], "Living Room");
+    assert_eq!(opening["time"]["watch"], "7:00pm");
+    assert_eq!(opening["ending"], Value::Null);
+
+    let table = opening["objects"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|object| object["name"] == "coffee table")
+        .unwrap();
+    assert_eq!(table["contents"], serde_json::json!(["watch"]));
+
+    let took_watch = &responses[2];
+    assert_eq!(took_watch["inventory"][0]["name"], "watch");
+    assert!(took_watch["objects"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|object| object["name"] != "watch"));
+
+    assert_eq!(responses[3]["room"]["name"], "Kitchen");
+    assert_eq!(responses[4]["messages"], serde_json::json!([]));
+    assert!(responses[5]["error"].is_string());
+}
+
+#[test]
+fn descriptions_are_written_out_as_the_player_would_read_them() {
+    let mut game = tea_time::new_game(Clock::manual());
+    game.step("east");
+    game.state.clock.advance(Duration::from_secs(150));
+    let description = protocol::observe(&game)["room"]["description"].clone();
+    assert!(description
+        .as_str()
+        .unwrap()
+        .starts_with("The moon has slipped past the window"));
+}
@@ -0,0 +1,43 @@
+use text_engine::clock::Clock;
+use text_engine::worlds::tea_time;
+
+#[test]
+fn descriptions_follow_the_world() {
+    let mut game = tea_time::new_game(Clock::manual());
+    // the couch and table are scenery, and the watch is still on the table
+    let look = game.step("look").join("\n");
+    assert!(!look.contains("You see"), "{}", look);
+    assert!(look.ends_with("\nExits: east, north."), "{}", look);
+
+    game.step("examine coffee table");
+    game.step("take watch");
+    let kitchen = game.step("east").join("\n");
+    assert!(!kitchen.contains("You see"), "{}", kitchen);
+    assert_eq!(game.step("drop watch"), vec!["You drop the watch."]);
+    assert_eq!(game.step("drop watch"), vec!["You don't have the watch."]);
+
+    game.step("examine cupboard");
+    game.step("take mug");
+    game.step("drop mug");
+    let look = game.step("look").join("\n");
+    assert!(
+        look.contains(" You see the watch and the mug here.\n"),
+        "{}",
+        look
+    );
+    // entering says the same as looking
+    game.step("west");
+    assert!(game.step("east")[0].ends_with(&look), "{}", look);
+
+    game.step("west");
+    let hallway = game.step("north").join("\n");
+    assert!(
+        hallway.ends_with("\nExits: south, north (closed), east."),
+        "{}",
+        hallway
+    );
+    game.step("wait 181 minutes");
+    let look = game.step("look").join("\n");
+    assert!(look.ends_with("\nExits: south, north, east."), "{}", look);
+    assert!(game.step("north")[0].contains("The cat and the neighbor are here."));
+}
@@ -0,0 +1,124 @@
+use std::path::Path;
+use text_engine::clock::Clock;
+use text_engine::game::Game;
+use text_engine::worlds::{file, tea_time};
+
+fn larder() -> Game {
+    file::load(Path::new("tests/worlds/larder.json"), Clock::manual()).unwrap()
+}
+
+#[test]
+fn rules_run_around_the_action_most_specific_first() {
+    let mut game = larder();
+    assert_eq!(
+        game.step("take jam"),
+        vec![
+            "Your fingers stick to the jar.",
+            "You reach out.",
+            "You take the jam.",
+            "Now your hands are sticky."
+        ]
+    );
+    assert!(game.player.has("jam"));
+    assert!(game.state.has_flag("sticky"));
+    assert_eq!(
+        game.state.rules_fired,
+        vec!["sticky jam", "reaching", "sticky hands"]
+    );
+    // the trace only covers the last turn
+    game.step("look");
+    assert!(game.state.rules_fired.is_empty());
+}
+
+#[test]
+fn rules_can_veto_replace_and_redirect_actions() {
+    let mut game = larder();
+    assert_eq!(
+        game.step("examine mouse"),
+        vec!["The mouse darts behind a jar."]
+    );
+
+    game.state.set_flag("stale");
+    assert_eq!(
+        game.step("take bread"),
+        vec!["You reach out.", "The bread crumbles to dust."]
+    );
+    assert!(!game.player.has("bread"));
+
+    // the redirect is more specific than "reaching", so it goes first and ends the action
+    assert_eq!(game.step("take mouse"), vec!["Squeak."]);
+    assert_eq!(game.state.rules_fired, vec!["rules[5]"]);
+
+    // rules that redirect to each other give up instead of looping
+    game.state.clear_flag("stale");
+    game.step("take jam");
+    game.step("take bread");
+    game.step("drop jam");
+    assert!(game.player.has("jam") && game.player.has("bread"));
+}
+
+#[test]
+fn world_files_report_bad_rules() {
+    let world = |rule: &str| {
+        format!(
+            r#"{{ "rooms": [{{ "name": "Larder", "desc": "" }}], "rules": [{}] }}"#,
+            rule
+        )
+    };
+    let error = |rule: &str| file::parse(&world(rule), Clock::manual()).err().unwrap();
+    assert_eq!(
+        error(r#"{ "phase": "before", "verb": "lick" }"#),
+        "rules[0]: there is no verb `lick`"
+    );
+    assert_eq!(
+        error(r#"{ "phase": "after", "room": "Attic" }"#),
+        "rules[0]: there is no room called `Attic`"
+    );
+}
+
+#[test]
+fn the_cat_guards_its_sugar() {
+    let mut game = tea_time::new_game(Clock::manual());
+    let unit_11 = game.world.room_named("Unit 11").unwrap();
+    game.player.go(unit_11);
+    assert_eq!(
+        game.step("take sugar"),
+        vec!["You reach for the sugar. The cat hisses and swats your hand away. Perhaps you could just ask."]
+    );
+    assert_eq!(
+        game.state.rules_fired,
+        vec!["taking the sugar while the cat watches"]
+    );
+    game.step("ask cat for sugar");
+    assert!(game.player.has("sugar"));
+    game.step("take sugar");
+    assert!(game.state.rules_fired.is_empty());
+}
+
+#[test]
+fn after_rules_wait_for_the_action_to_work() {
+    let mut game = larder();
+    game.step("take jam");
+    game.state.clear_flag("sticky");
+    game.step("take jam");
+    assert_eq!(game.state.rules_fired, vec!["sticky jam", "reaching"]);
+    assert!(!game.state.has_flag("sticky"));
+}
+
+#[test]
+fn rules_follow_the_player_on_a_trip() {
+    let world = r#"{
+        "rooms": [
+            { "name": "Hall", "desc": "", "doors": [{ "direction": "east", "to": "Study" }] },
+            { "name": "Study", "desc": "", "doors": [{ "direction": "east", "to": "Attic" }, { "direction": "west", "to": "Hall" }] },
+            { "name": "Attic", "desc": "", "doors": [{ "direction": "west", "to": "Study" }] }
+        ],
+        "rules": [{ "name": "creaky floor", "phase": "after", "verb": "west", "effects": [{ "set_flag": "creaked" }] }]
+    }"#;
+    let mut game = file::parse(world, Clock::manual()).unwrap();
+    game.step("east");
+    game.step("east");
+    game.step("go to hall");
+    assert_eq!(game.player.get_curr_room(&game.rooms).name, "Hall");
+    assert!(game.state.has_flag("creaked"));
+}
@@ -0,0 +1,45 @@
+use std::time::Duration;
+use text_engine::clock::Clock;
+use text_engine::worlds::tea_time;
+
+#[test]
+fn followers_skip_the_stops_they_missed() {
+    let mut game = tea_time::new_game(Clock::manual());
+    game.step("north");
+    game.step("east");
+    let stairwell = game.player.at;
+    let neighbor = game.world.character_named(stairwell, "neighbor").unwrap();
+    game.world
+        .character_mut(neighbor, &mut game.rooms)
+        .unwrap()
+        .is_following = true;
+
+    game.state.clock.advance(Duration::from_secs(200));
+    game.step("west");
+    let hallway = game.player.at;
+    game.world
+        .character_mut(neighbor, &mut game.rooms)
+        .unwrap()
+        .is_following = false;
+    game.step("look");
+    // the neighbor stays put instead of going on to Unit 11, as they would have at 181
+    assert_eq!(game.world.locate_character(neighbor), Some(hallway));
+}
+
+#[test]
+fn the_neighbor_only_mentions_the_cat_once_home() {
+    let mut game = tea_time::new_game(Clock::manual());
+    game.step("north");
+    game.step("east");
+    assert!(game.step("talk to neighbor")[0].contains("I'll be home by ten"));
+
+    game.state.clock.advance(Duration::from_secs(180));
+    game.step("west");
+    assert_eq!(
+        game.step("talk to neighbor")[0],
+        "`Oh, hello! Sorry, I was out getting more candles.`"
+    );
+    game.step("wait");
+    game.step("north");
+    assert!(game.step("talk to neighbor")[0].ends_with("I see you've met my cat.`"));
+}
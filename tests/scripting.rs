@@ -0,0 +1,140 @@
+use std::path::Path;
+use std::time::Duration;
+use text_engine::clock::Clock;
+use text_engine::game::Game;
+use text_engine::worlds::file;
+
+fn workshop() -> Game {
+    file::load(Path::new("tests/worlds/workshop.json"), Clock::manual()).unwrap()
+}
+
+#[test]
+fn scripts_run_around_verbs() {
+    let mut game = workshop();
+    // the stove has no USE of its own, so the script stops the verb after doing its part
+    assert_eq!(game.step("use stove"), vec!["The stove roars to life."]);
+    assert!(game.state.has_flag("lit"));
+    assert_eq!(game.state.sounds, vec!["whoosh.mp3"]);
+    assert_eq!(game.step("use stove"), vec!["The stove is already lit."]);
+
+    assert_eq!(game.step("take bell"), vec!["The bell is bolted down."]);
+    assert!(!game.player.has("bell"));
+    assert_eq!(
+        game.step("get jar"),
+        vec!["You take the jar.", "The jar is heavier than it looks."]
+    );
+}
+
+#[test]
+fn scripts_run_on_rooms_characters_and_timers() {
+    let mut game = workshop();
+    game.step("take jar");
+    let messages = game.step("north");
+    assert_eq!(
+        &messages[1..],
+        [
+            "The jar clinks as you walk.",
+            "A leaf blows into your hand."
+        ]
+    );
+    assert!(game.player.has("leaf"));
+    assert_eq!(
+        game.step("talk to owl"),
+        vec!["Hoo.", "The owl blinks 0 times."]
+    );
+
+    game.state.clock.advance(Duration::from_secs(5));
+    assert_eq!(game.step("look").last().unwrap(), "A clock strikes five.");
+    assert!(!game
+        .step("look")
+        .contains(&"A clock strikes five.".to_owned()));
+}
+
+#[test]
+fn script_errors_say_where_they_were_written() {
+    let mut game = workshop();
+    let messages = game.step("examine crate");
+    assert_eq!(messages.len(), 2, "{:?}", messages);
+    assert!(
+        messages[0].starts_with(
+            "[Script error at tests/worlds/workshop.json: rooms[0].objects[4].scripts[\"before examine\"]: "
+        ),
+        "{}",
+        messages[0]
+    );
+    assert!(messages[0].contains("nails"), "{}", messages[0]);
+    assert!(messages[0].contains("line 2"), "{}", messages[0]);
+    // the failed script's "Creak." never happened, and the verb still did
+    assert_eq!(messages[1], "A crate.");
+
+    let messages = game.step("x wheel");
+    assert!(
+        messages[0].contains("scripts[\"before x\"]"),
+        "{}",
+        messages[0]
+    );
+
+    let broken = r#"{ "rooms": [{ "name": "Cellar", "desc": "Dark.", "scripts": { "enter": "say(\"hi\"" } }] }"#;
+    let e = file::parse(broken, Clock::manual()).err().unwrap();
+    assert!(e.starts_with("rooms[0].scripts[\"enter\"]: "), "{}", e);
+    let unknown = r#"{ "rooms": [{ "name": "Cellar", "desc": "Dark.", "objects": [{ "name": "rock", "desc": "A rock.", "scripts": { "before lick": "" } }] }] }"#;
+    let e = file::parse(unknown, Clock::manual()).err().unwrap();
+    assert_eq!(
+        e,
+        "rooms[0].objects[0].scripts[\"before lick\"]: there is no verb `lick`"
+    );
+}
+
+#[test]
+fn scripts_roll_dice_from_the_seed() {
+    let world = r#"{ "rooms": [{ "name": "Den", "desc": "", "objects": [
+        { "name": "die", "desc": "A die.", "scripts": { "after examine": "say(`${random(6) + 1}`);" } }
+    ] }] }"#;
+    let rolls = |seed| {
+        let mut game = file::parse(world, Clock::manual()).unwrap();
+        game.state.reseed(seed);
+        (0..10)
+            .map(|_| game.step("examine die")[1].clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(rolls(1), rolls(1));
+    assert_ne!(rolls(1), rolls(2));
+    assert!(rolls(3)
+        .iter()
+        .all(|roll| ["1", "2", "3", "4", "5", "6"].contains(&roll.as_str())));
+}
+
+#[test]
+fn after_scripts_wait_for_the_verb_to_work() {
+    let world = r#"{ "rooms": [{ "name": "Den", "desc": "", "objects": [
+        { "name": "anvil", "desc": "An anvil.", "scripts": { "after take": "say(`Oof.`);" } },
+        { "name": "feather", "desc": "A feather.", "can_take": true, "scripts": { "after take": "say(`Whee.`);" } }
+    ] }] }"#;
+    let mut game = file::parse(world, Clock::manual()).unwrap();
+    assert!(!game.step("take anvil").contains(&"Oof.".to_owned()));
+    assert!(game.step("take feather").contains(&"Whee.".to_owned()));
+}
+
+#[test]
+fn look_is_another_word_for_examine() {
+    let world = r#"{ "rooms": [{ "name": "Den", "desc": "", "objects": [
+        { "name": "die", "desc": "A die.", "scripts": { "after examine": "say(`Six.`);" } }
+    ] }] }"#;
+    let mut game = file::parse(world, Clock::manual()).unwrap();
+    assert_eq!(game.step("look at die"), vec!["A die.", "Six."]);
+}
+
+#[test]
+fn scripts_that_fail_undo_only_their_own_changes() {
+    let world = r#"{ "rooms": [{ "name": "Den", "desc": "", "objects": [
+        { "name": "lever", "desc": "A lever.", "scripts": { "after examine": "set_flag(`pulled`); destroy(`bell`); oops();" } },
+        { "name": "bell", "desc": "A bell.", "scripts": { "after examine": "set_flag(`rung`);" } }
+    ] }] }"#;
+    let mut game = file::parse(world, Clock::manual()).unwrap();
+    game.step("examine bell");
+    game.step("examine lever");
+    assert!(game.state.has_flag("rung"));
+    assert!(!game.state.has_flag("pulled"));
+    let here = game.player.get_curr_room(&game.rooms);
+    assert!(here.inventory.contains("bell"));
+}
@@ -0,0 +1,37 @@
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::thread;
+use std::time::Duration;
+use text_engine::server::{clean_line, Server};
+use text_engine::worlds::tea_time;
+
+#[test]
+fn telnet_line_editing() {
+    // IAC DO ECHO, a typo fixed with backspace, and a CRLF ending
+    let raw = b"\xff\xfd\x01lookk\x08\r\n";
+    assert_eq!(clean_line(raw), "look");
+}
+
+#[test]
+fn sessions_are_separate() {
+    let mut server = Server::bind("127.0.0.1:0", tea_time::new_game).unwrap();
+    server.idle_timeout = Duration::from_millis(500);
+    let addr = server.local_addr().unwrap();
+    thread::spawn(move || server.run());
+
+    let mut first = TcpStream::connect(addr).unwrap();
+    let mut second = TcpStream::connect(addr).unwrap();
+    first.write_all(b"east\r\nquit\r\n").unwrap();
+    let mut output = String::new();
+    first.read_to_string(&mut output).unwrap();
+    assert!(output.contains("Kitchen"));
+    assert!(output.ends_with("Goodbye!\r\n"));
+
+    // the second player is still in the living room, and is dropped once idle
+    second.write_all(b"look\r\n").unwrap();
+    let mut output = String::new();
+    second.read_to_string(&mut output).unwrap();
+    assert!(!output.contains("Kitchen"));
+    assert!(output.contains("Living Room"));
+    assert!(output.contains("Disconnected for inactivity"));
+}
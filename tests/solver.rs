@@ -0,0 +1,63 @@
+use std::path::Path;
+use text_engine::clock::Clock;
+use text_engine::game::Game;
+use text_engine::solver::Solver;
+use text_engine::worlds::{file, tea_time};
+
+fn new_game() -> Game {
+    tea_time::new_game(Clock::manual())
+}
+
+#[test]
+fn tea_time_cannot_be_won_before_unit_11_opens() {
+    let mut solver = Solver::new(new_game);
+    solver.time_limit = Some(tea_time::DOOR_CLOSED_LENGTH);
+    let report = solver.solve();
+    assert!(report.complete);
+    assert_eq!(report.shortest_win, None);
+}
+
+/// Explores the whole world, which takes a while without optimizations:
+/// cargo test --release -- --ignored
+#[test]
+#[ignore]
+fn tea_time_is_winnable() {
+    let report = Solver::new(new_game).solve();
+    assert!(report.complete);
+    let path = report.shortest_win.unwrap();
+    for step in &["use kettle", "put hot water in mug", "ask cat about sugar"] {
+        assert!(path.iter().any(|action| action == step), "{:?}", path);
+    }
+    assert!(report.earliest_win.unwrap() > tea_time::DOOR_CLOSED_LENGTH);
+
+    // the winning path replays to a win
+    let mut game = new_game();
+    for action in &path {
+        game.step(action);
+    }
+    assert!(game.state.player_won());
+}
+
+#[test]
+fn world_files_can_be_solved() {
+    let cellar = file::load(Path::new("tests/worlds/cellar.json"), Clock::manual()).unwrap();
+    let report = Solver::new(move || cellar.clone()).solve();
+    assert!(report.complete);
+    assert_eq!(report.shortest_win, Some(vec!["take lantern".to_owned()]));
+}
+
+#[test]
+fn waiting_counts_scripted_timers() {
+    let bell = r#"{
+        "time_limit": 10,
+        "goal": { "flag": "rang" },
+        "rooms": [{ "name": "Belfry", "desc": "A quiet belfry." }],
+        "timers": [{ "at": 5, "script": "set_flag(\"rang\");" }]
+    }"#;
+    let game = file::parse(bell, Clock::manual()).unwrap();
+    let report = Solver::new(move || game.clone()).solve();
+    assert!(report.complete);
+    // the bell rings as the next step begins
+    let path = report.shortest_win.unwrap();
+    assert_eq!(path[0], "wait 5 minutes");
+}
@@ -0,0 +1,31 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+use text_engine::clock::Clock;
+use text_engine::game::Game;
+use text_engine::transcript::check_file;
+use text_engine::worlds::tea_time;
+
+fn new_game() -> Game {
+    tea_time::new_game(Clock::manual())
+}
+
+/// Replays every golden file in tests/transcripts. Run with BLESS=1 to rewrite them.
+#[test]
+fn tea_time_transcripts() {
+    let bless = env::var_os("BLESS").is_some();
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/transcripts");
+    let mut paths: Vec<_> = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension() == Some("txt".as_ref()))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty());
+
+    let failures: Vec<String> = paths
+        .iter()
+        .filter_map(|path| check_file(path, new_game, bless).err())
+        .collect();
+    assert!(failures.is_empty(), "\n{}", failures.join("\n\n"));
+}
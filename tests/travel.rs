@@ -0,0 +1,113 @@
+use text_engine::clock::Clock;
+use text_engine::graph::{self, Step};
+use text_engine::room::RoomID;
+use text_engine::worlds::tea_time;
+
+#[test]
+fn graph_follows_open_doors() {
+    let game = tea_time::new_game(Clock::manual());
+    let living_room = game.world.room_named("living room").unwrap();
+    let kitchen = game.world.room_named("Kitchen").unwrap();
+    let hallway = game.world.room_named("hallway").unwrap();
+    let unit_11 = game.world.room_named("unit 11").unwrap();
+    let stairwell = game.world.room_named("stairwell").unwrap();
+
+    assert_eq!(
+        graph::reachable(&game.rooms, living_room),
+        vec![living_room, kitchen, hallway, stairwell]
+    );
+    assert_eq!(
+        graph::shortest_path(&game.rooms, kitchen, hallway),
+        Some(vec![
+            Step {
+                direction: "west",
+                to: living_room
+            },
+            Step {
+                direction: "north",
+                to: hallway
+            },
+        ])
+    );
+    // Unit 11 is locked until later in the evening
+    assert_eq!(graph::shortest_path(&game.rooms, kitchen, unit_11), None);
+    assert_eq!(
+        graph::shortest_path(&game.rooms, kitchen, kitchen),
+        Some(vec![])
+    );
+    assert_eq!(game.world.room_named("attic"), None::<RoomID>);
+}
+
+#[test]
+fn go_to_walks_the_way_and_return_comes_back() {
+    let mut game = tea_time::new_game(Clock::manual());
+    assert_eq!(
+        game.step("go to the kitchen"),
+        vec!["You don't know the way to kitchen."]
+    );
+    assert_eq!(
+        game.step("return"),
+        vec!["You haven't been anywhere to go back to."]
+    );
+    game.step("east");
+    game.step("west");
+    game.step("north");
+
+    let trip = game.step("go to the kitchen");
+    assert_eq!(trip.len(), 2);
+    assert!(trip[1].contains("\nKitchen\n"), "{:?}", trip);
+    assert_eq!(game.state.clock.elapsed_secs(), 2);
+    assert_eq!(game.step("go to kitchen"), vec!["You're already there."]);
+
+    game.step("return");
+    assert_eq!(game.player.at, game.world.room_named("hallway").unwrap());
+    // the whole trip is undone at once
+    game.step("undo");
+    assert_eq!(game.player.at, game.world.room_named("kitchen").unwrap());
+}
+
+#[test]
+fn travel_stops_when_something_happens() {
+    let mut game = tea_time::new_game(Clock::manual());
+    game.step("east");
+    game.step("west");
+    game.step("north");
+    game.update = |_, _, _, state| match state.clock.elapsed_secs() {
+        1 => vec!["Your phone buzzes.".to_owned()],
+        _ => vec![],
+    };
+    let trip = game.step("go to kitchen");
+    assert_eq!(
+        &trip[1..],
+        ["Your phone buzzes.", "You stop before you get there."]
+    );
+    assert_eq!(
+        game.player.at,
+        game.world.room_named("living room").unwrap()
+    );
+}
+
+#[test]
+fn travel_only_stops_for_someone_new() {
+    let mut game = tea_time::new_game(Clock::manual());
+    game.step("east");
+    game.step("west");
+    game.step("north");
+    let living_room = game.world.room_named("living room").unwrap();
+    let cat = game
+        .rooms
+        .iter()
+        .find_map(|room| room.find_character("cat"))
+        .unwrap()
+        .id;
+    game.world.move_character(cat, living_room, &mut game.rooms);
+
+    let trip = game.step("go to kitchen");
+    assert_eq!(trip.last().unwrap(), "You stop before you get there.");
+    assert_eq!(game.player.at, living_room);
+
+    // the cat is still there, but the player has seen it now
+    game.step("north");
+    game.step("go to kitchen");
+    assert_eq!(game.player.at, game.world.room_named("kitchen").unwrap());
+}
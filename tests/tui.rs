@@ -0,0 +1,38 @@
+#![cfg(feature = "tui")]
+
+use text_engine::clock::Clock;
+use text_engine::tui::{inventory_lines, status_line, wrap};
+use text_engine::worlds::tea_time;
+
+#[test]
+fn status_bar_and_inventory_panel() {
+    let mut game = tea_time::new_game(Clock::manual());
+    game.step("wait 65 minutes");
+    assert_eq!(
+        status_line(&game, Some(tea_time::GAME_LENGTH)),
+        " Living Room | 8:05pm | 3:55 left"
+    );
+
+    for input in &[
+        "east",
+        "examine cupboard",
+        "take mug",
+        "examine tea tin",
+        "take tea bag",
+    ] {
+        game.step(input);
+    }
+    game.step("put tea bag in mug");
+    assert_eq!(
+        inventory_lines(&game.player),
+        vec![" Inventory", " - mug", "     tea bag"]
+    );
+}
+
+#[test]
+fn wraps_at_spaces() {
+    assert_eq!(
+        wrap("a kettle and a tea tin", 10),
+        vec!["a kettle", "and a tea", "tin"]
+    );
+}
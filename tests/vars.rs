@@ -0,0 +1,121 @@
+use std::path::Path;
+use std::time::Duration;
+use text_engine::clock::Clock;
+use text_engine::game::{Ending, Game};
+use text_engine::save::Save;
+use text_engine::vars::{Value, Vars};
+use text_engine::worlds::{file, tea_time};
+
+fn vault() -> Game {
+    file::load(Path::new("tests/worlds/vault.json"), Clock::manual()).unwrap()
+}
+
+#[test]
+fn variables_are_typed() {
+    let mut vars = Vars::default();
+    assert_eq!(vars.int("coins"), 0);
+    vars.add("coins", 2);
+    vars.set("name", Value::Str("Ada".to_owned()));
+    vars.set("lit", Value::Bool(true));
+    vars.set("lit at", Value::Time(40));
+    assert_eq!(vars.int("coins"), 2);
+    assert_eq!(vars.string("name"), Some("Ada"));
+    assert!(vars.bool("lit") && !vars.bool("name"));
+    assert_eq!(vars.time("lit at"), Some(40));
+    assert_eq!(
+        vars.describe(),
+        "coins = 2\nlit = true\nlit at = time 40\nname = \"Ada\""
+    );
+}
+
+#[test]
+fn flags_and_the_tea_are_variables() {
+    let mut game = tea_time::new_game(Clock::manual());
+    game.state.set_flag("met cat");
+    assert_eq!(game.state.vars.get("met cat"), Some(&Value::Bool(true)));
+    game.state.clear_flag("met cat");
+    assert!(!game.state.has_flag("met cat"));
+
+    for line in [
+        "east",
+        "examine counter",
+        "take water",
+        "put water in kettle",
+        "use kettle",
+        "examine tea tin",
+        "take tea bag",
+    ] {
+        game.step(line);
+    }
+    for line in [
+        "examine cupboard",
+        "take mug",
+        "take hot water",
+        "put hot water in mug",
+    ] {
+        game.step(line);
+    }
+    game.state.clock.advance(Duration::from_secs(30));
+    game.step("put tea bag in mug");
+    assert_eq!(game.state.vars.time("tea time"), Some(30));
+}
+
+#[test]
+fn rules_scripts_and_doors_use_variables() {
+    let mut game = vault();
+    assert_eq!(game.step("north"), vec!["The guard shakes his head."]);
+    assert!(game
+        .step("take coin")
+        .contains(&"You take the coin.".to_owned()));
+    assert_eq!(game.state.vars.int("coins"), 2);
+    assert!(!game.state.has_flag("smiled"));
+
+    let messages = game.step("take purse");
+    assert_eq!(messages.last().unwrap(), "Coins: 3");
+    assert_eq!(game.state.vars.string("mood"), Some("pleased"));
+    assert!(game.state.has_flag("smiled"));
+    assert_eq!(game.player.get_curr_room(&game.rooms).name, "Lobby");
+    game.step("north");
+    assert_eq!(game.player.get_curr_room(&game.rooms).name, "Vault");
+}
+
+#[test]
+fn saves_keep_variables() {
+    let mut game = vault();
+    game.step("take coin");
+    game.state.vars.set("cheat", Value::Int(99));
+    let save = Save::of(&game, Some("tests/worlds/vault.json".to_owned()));
+    let text = serde_json::to_string(&save).unwrap();
+    assert!(
+        text.contains(r#""vars":{"cheat":{"int":99},"coins":{"int":2},"mood":{"str":"grumpy"}}"#),
+        "{}",
+        text
+    );
+
+    let mut resumed = vault();
+    serde_json::from_str::<Save>(&text)
+        .unwrap()
+        .replay(&mut resumed);
+    assert_eq!(resumed.state.vars, game.state.vars);
+
+    // saves from before variables were written down still replay
+    let old = r#"{ "world": null, "seed": 0, "elapsed": 0, "commands": [[0, "take coin"]] }"#;
+    let mut resumed = vault();
+    serde_json::from_str::<Save>(old)
+        .unwrap()
+        .replay(&mut resumed);
+    assert_eq!(resumed.state.vars.int("coins"), 2);
+}
+
+#[test]
+fn endings_are_flags() {
+    let world = r#"{
+        "rooms": [{ "name": "Vault", "desc": "", "objects": [{ "name": "crown", "desc": "A crown.", "can_take": true }] }],
+        "rules": [{ "phase": "after", "verb": "take", "object": "crown", "effects": [{ "set_flag": "won" }] }]
+    }"#;
+    let mut game = file::parse(world, Clock::manual()).unwrap();
+    assert_eq!(game.ending(), None);
+    game.step("take crown");
+    assert_eq!(game.ending(), Some(Ending::Won));
+    assert!(game.state.vars.bool("won"));
+}
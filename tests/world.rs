@@ -0,0 +1,104 @@
+use text_engine::clock::Clock;
+use text_engine::command::Command;
+use text_engine::parser::Parser;
+use text_engine::world::{Entity, Location};
+use text_engine::worlds::tea_time;
+
+#[test]
+fn ids_follow_things_around() {
+    let mut game = tea_time::new_game(Clock::manual());
+    let living_room = game.world.room_named("Living Room").unwrap();
+    let watch = game
+        .world
+        .object_named(Location::Room(living_room), "watch")
+        .unwrap();
+    assert_eq!(game.world.locate(watch), Some(Location::Room(living_room)));
+
+    game.step("examine coffee table");
+    game.step("take watch");
+    assert_eq!(
+        game.world.locate(watch),
+        Some(Location::Player(game.player.id))
+    );
+    assert_eq!(
+        game.world
+            .object(watch, &game.player, &game.rooms)
+            .unwrap()
+            .name,
+        "watch"
+    );
+    assert_eq!(
+        game.world
+            .object_named(Location::Room(living_room), "watch"),
+        None
+    );
+
+    game.step("east");
+    game.step("drop watch");
+    let kitchen = game.world.room_named("kitchen").unwrap();
+    assert_eq!(game.world.locate(watch), Some(Location::Room(kitchen)));
+    game.step("undo");
+    assert_eq!(
+        game.world.locate(watch),
+        Some(Location::Player(game.player.id))
+    );
+}
+
+#[test]
+fn characters_and_doors_are_indexed() {
+    let game = tea_time::new_game(Clock::manual());
+    let hallway = game.world.room_named("hallway").unwrap();
+    let unit_11 = game.world.room_named("unit 11").unwrap();
+    let door = game.world.door_towards(hallway, "north").unwrap();
+    assert_eq!(game.world.locate_door(door), Some(hallway));
+    assert_eq!(game.world.door(door, &game.rooms).unwrap().target, unit_11);
+
+    let cat = game.world.character_named(unit_11, "cat").unwrap();
+    assert_eq!(game.world.locate_character(cat), Some(unit_11));
+    let sugar = game
+        .world
+        .object_named(Location::Character(cat), "sugar")
+        .unwrap();
+    assert_eq!(game.world.locate(sugar), Some(Location::Character(cat)));
+}
+
+#[test]
+fn parser_resolves_names_once() {
+    let game = tea_time::new_game(Clock::manual());
+    let living_room = game.world.room_named("living room").unwrap();
+    let table = game
+        .world
+        .object_named(Location::Room(living_room), "coffee table")
+        .unwrap();
+
+    let mut command: Command = Parser::parse("examine coffee table").unwrap();
+    Parser::resolve(&mut command, &game.player, &game.world);
+    assert_eq!(command.target, Some(Entity::Object(table)));
+
+    let mut command = Parser::parse("examine cat").unwrap();
+    Parser::resolve(&mut command, &game.player, &game.world);
+    assert_eq!(command.target, None);
+}
+
+#[test]
+fn indexes_keep_up_without_a_reindex() {
+    let mut game = tea_time::new_game(Clock::manual());
+    let kitchen = game.world.room_named("kitchen").unwrap();
+    game.step("east");
+    game.step("examine cupboard");
+    game.step("take mug");
+    let me = Location::Player(game.player.id);
+    let mug = game.world.object_named(me, "mug").unwrap();
+    assert_eq!(
+        game.world.object_named(Location::Room(kitchen), "mug"),
+        None
+    );
+
+    game.step("drop mug");
+    assert_eq!(game.world.object_named(me, "mug"), None);
+    assert_eq!(
+        game.world.object_named(Location::Room(kitchen), "mug"),
+        Some(mug)
+    );
+    assert_eq!(game.world.locate(mug), Some(Location::Room(kitchen)));
+}
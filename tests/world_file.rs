@@ -0,0 +1,82 @@
+use std::path::Path;
+use std::time::Duration;
+use text_engine::clock::Clock;
+use text_engine::game::Ending;
+use text_engine::worlds::file;
+
+#[test]
+fn plays_a_world_file() {
+    let mut game = file::load(Path::new("tests/worlds/cellar.json"), Clock::manual()).unwrap();
+    assert!(game.describe_room().contains("Damp stone walls"));
+    assert_eq!(game.step("go north"), vec!["The trapdoor is stuck."]);
+
+    // doors open at the end of the turn their time comes
+    game.state.clock.advance(Duration::from_secs(10));
+    game.step("look");
+    game.step("go north");
+    assert_eq!(game.player.get_curr_room(&game.rooms).name, "Kitchen");
+
+    game.step("go south");
+    game.step("examine crate");
+    game.step("take lantern");
+    assert_eq!(game.ending(), Some(Ending::Won));
+}
+
+#[test]
+fn runs_out_of_time() {
+    let mut game = file::load(Path::new("tests/worlds/cellar.json"), Clock::manual()).unwrap();
+    game.state.clock.advance(Duration::from_secs(30));
+    game.step("look");
+    assert_eq!(game.ending(), Some(Ending::Lost));
+}
+
+#[test]
+fn worlds_choose_which_endings_can_be_undone() {
+    let mut game = file::load(Path::new("tests/worlds/cellar.json"), Clock::manual()).unwrap();
+    assert!(game.history.after_loss && !game.history.after_win);
+    game.state.clock.advance(Duration::from_secs(30));
+    game.step("look");
+    assert!(game.can_undo_ending(Ending::Lost));
+
+    let text = std::fs::read_to_string("tests/worlds/cellar.json").unwrap();
+    let text = text.replacen('{', r#"{ "undo": { "after_loss": false }, "#, 1);
+    let mut game = file::parse(&text, Clock::manual()).unwrap();
+    assert_eq!(game.history.depth, 20);
+    game.state.clock.advance(Duration::from_secs(30));
+    game.step("look");
+    assert_eq!(game.ending(), Some(Ending::Lost));
+    assert!(!game.can_undo_ending(Ending::Lost));
+    assert_eq!(
+        game.step("undo"),
+        vec!["The story is over; it can't be undone."]
+    );
+}
+
+#[test]
+fn errors_say_where() {
+    let error = file::parse(
+        "{\n  \"rooms\": [{ \"name\": \"Hall\" }]\n}",
+        Clock::manual(),
+    )
+    .err()
+    .unwrap();
+    assert!(error.contains("missing field `desc`"), "{}", error);
+    assert!(error.contains("line 2"), "{}", error);
+
+    let error = file::parse(
+        r#"{ "rooms": [{ "name": "Hall", "desc": "", "doors": [{ "direction": "up", "to": "Attic" }] }] }"#,
+        Clock::manual(),
+    )
+    .err()
+    .unwrap();
+    assert_eq!(error, "there is no room called `Attic`");
+
+    let error = file::load(Path::new("tests/worlds/missing.json"), Clock::manual())
+        .err()
+        .unwrap();
+    assert!(
+        error.starts_with("tests/worlds/missing.json: "),
+        "{}",
+        error
+    );
+}